@@ -11,6 +11,9 @@ pub enum DexError {
     #[error("Parse: {0}")]
     Parse(String),
 
+    #[error("Invalid request: {0}")]
+    Validation(String),
+
     #[error("Exchange error {code:?}: {msg}")]
     Exchange { code: Option<i64>, msg: String },
 
@@ -10,7 +10,7 @@ pub mod rt_tokio; // feature-gated inside file
 pub mod traits;
 pub mod ws;
 
-pub use traits::{PerpDex, Position, StreamEvent, StreamKind};
+pub use traits::{ConnectionState, PerpDex, Position, StreamEvent, StreamKind, SubscriptionHandle};
 
 /* Re-export types from sibling crate for convenience */
 pub use dex_rs_types as types;
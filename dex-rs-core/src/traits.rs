@@ -1,7 +1,7 @@
 use crate::DexError;
 use async_trait::async_trait;
 use dex_rs_types::*;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, watch};
 
 #[derive(Debug, Clone)]
 pub struct Position {
@@ -16,8 +16,31 @@ pub enum StreamKind {
     Trades,
     Bbo,
     L2Book,
+    /// L2 book with coarser price levels. Messages do not carry the aggregation, so a
+    /// coin's book can only be streamed with one aggregation at a time; subscribing
+    /// with another while it is active fails with [`DexError::Validation`].
+    L2BookAggregated(BookAggregation),
     Orders,
     Fills,
+    /// Slice fills of the user's TWAP orders
+    TwapSliceFills,
+    /// Mid prices of every coin
+    AllMids,
+    Candle(CandleInterval),
+    /// Exchange notifications for the user
+    Notification,
+    /// Aggregate account snapshot (clearinghouse state and open orders)
+    WebData2,
+    /// The user's fills, fundings, liquidations and non-user cancels
+    UserEvents,
+    UserFundings,
+    /// Deposits, withdrawals, transfers and other non-funding ledger changes
+    UserNonFundingLedgerUpdates,
+    /// Perp or spot asset context of a coin
+    ActiveAssetCtx,
+    /// The user's leverage and trade limits on a coin
+    ActiveAssetData,
+    UserTwapHistory,
 }
 
 #[derive(Debug, Clone)]
@@ -44,6 +67,69 @@ pub struct FillEvent {
     pub fee: String,
     pub hash: String,
     pub user: String,
+    /// Replayed history sent when the subscription starts, not a new fill
+    pub is_snapshot: bool,
+}
+
+/// Funding payment on a position
+#[derive(Debug, Clone)]
+pub struct FundingEvent {
+    pub coin: String,
+    pub usdc: String,
+    pub szi: String,
+    pub funding_rate: String,
+    pub time: u64,
+    /// Replayed history sent when the subscription starts
+    pub is_snapshot: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct LiquidationEvent {
+    pub lid: u64,
+    pub liquidator: String,
+    pub liquidated_user: String,
+    pub liquidated_ntl_pos: String,
+    pub liquidated_account_value: String,
+}
+
+/// Non-funding ledger change, e.g. a deposit, withdrawal or transfer
+#[derive(Debug, Clone)]
+pub struct LedgerUpdateEvent {
+    pub time: u64,
+    pub hash: String,
+    /// Delta type, e.g. `deposit`, `withdraw` or `internalTransfer`
+    pub kind: String,
+    /// USDC amount, when the delta has one
+    pub usdc: Option<String>,
+    /// Full delta as sent by the exchange
+    pub delta: serde_json::Value,
+    /// Replayed history sent when the subscription starts
+    pub is_snapshot: bool,
+}
+
+/// Account snapshot of the user
+#[derive(Debug, Clone)]
+pub struct WebData2Event {
+    pub user: String,
+    pub server_time: u64,
+    pub account_value: String,
+    pub total_margin_used: String,
+    pub withdrawable: String,
+    pub positions: Vec<Position>,
+    pub open_orders: Vec<OpenOrder>,
+}
+
+/// The user's leverage and trade limits on a coin
+#[derive(Debug, Clone)]
+pub struct ActiveAssetDataEvent {
+    pub user: String,
+    pub coin: String,
+    pub leverage: Leverage,
+    /// Maximum trade sizes as `[buy, sell]`
+    pub max_trade_szs: Vec<String>,
+    /// Available to trade as `[buy, sell]`
+    pub available_to_trade: Vec<String>,
+    pub mark_px: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -58,6 +144,117 @@ pub enum StreamEvent {
     L2(OrderBook),
     Order(OrderEvent),
     Fill(FillEvent),
+    TwapSliceFill {
+        twap_id: u64,
+        fill: FillEvent,
+    },
+    AllMids(AllMids),
+    Candle {
+        coin: String,
+        interval: CandleInterval,
+        candle: Candle,
+    },
+    Notification {
+        message: String,
+    },
+    WebData2(Box<WebData2Event>),
+    Funding(FundingEvent),
+    Liquidation(LiquidationEvent),
+    /// Order cancelled by the exchange rather than the user
+    NonUserCancel {
+        coin: String,
+        oid: u64,
+    },
+    LedgerUpdate(LedgerUpdateEvent),
+    ActiveAssetCtx {
+        coin: String,
+        ctx: Box<AssetCtx>,
+    },
+    ActiveSpotAssetCtx {
+        coin: String,
+        ctx: SpotAssetCtx,
+    },
+    ActiveAssetData(ActiveAssetDataEvent),
+    TwapHistory {
+        entry: TwapHistoryEntry,
+        is_snapshot: bool,
+    },
+    /// The exchange acknowledged the subscription
+    Connected,
+    /// The connection dropped; a reconnect follows unless `GaveUp` is sent
+    Disconnected {
+        reason: String,
+    },
+    /// Live again after a reconnect; events may have been missed in between
+    Resubscribed,
+    /// Reconnect attempts are exhausted and the stream has ended
+    GaveUp,
+}
+
+/// State of a subscription on its connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    /// Connected and acknowledged by the exchange
+    Connected,
+    /// Connection lost; waiting to reconnect
+    Reconnecting,
+    /// No longer served: unsubscribed, or the connection gave up reconnecting
+    Closed,
+}
+
+/// Handle to a live subscription; unsubscribes when dropped
+pub struct SubscriptionHandle {
+    state: watch::Receiver<ConnectionState>,
+    cancel: Option<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+impl SubscriptionHandle {
+    /// Create a handle that reports `state` and runs `cancel` once on unsubscribe
+    pub fn new(
+        state: watch::Receiver<ConnectionState>,
+        cancel: impl FnOnce() + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            state,
+            cancel: Some(Box::new(cancel)),
+        }
+    }
+
+    /// Current connection state
+    pub fn state(&self) -> ConnectionState {
+        *self.state.borrow()
+    }
+
+    /// Receiver notified on every connection state change
+    pub fn state_updates(&self) -> watch::Receiver<ConnectionState> {
+        self.state.clone()
+    }
+
+    /// Stop the subscription; equivalent to dropping the handle
+    pub fn unsubscribe(mut self) {
+        self.cancel();
+    }
+
+    fn cancel(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel();
+        }
+    }
+}
+
+impl Drop for SubscriptionHandle {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
+
+impl std::fmt::Debug for SubscriptionHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SubscriptionHandle")
+            .field("state", &self.state())
+            .finish()
+    }
 }
 
 #[async_trait]
@@ -85,9 +282,42 @@ pub trait PerpDex: Send + Sync {
 
     /* ---------- account ---------- */
     async fn place_order(&self, req: OrderReq) -> Result<OrderResponse, DexError>;
-    async fn cancel(&self, id: OrderId) -> Result<(), DexError>;
+
+    /// Place several orders in one request, returning a status per order
+    async fn place_orders(
+        &self,
+        reqs: Vec<OrderReq>,
+        grouping: Grouping,
+    ) -> Result<Vec<OrderStatusResult>, DexError>;
+    async fn cancel(&self, coin: &str, id: OrderId) -> Result<CancelStatus, DexError>;
+
+    /// Cancel an order by its client order ID
+    async fn cancel_by_cloid(&self, coin: &str, cloid: &str) -> Result<CancelStatus, DexError>;
+
+    /// Cancel several orders in one request, returning a status per order
+    async fn cancel_many(&self, reqs: Vec<CancelReq>) -> Result<Vec<CancelStatus>, DexError>;
     async fn positions(&self) -> Result<Vec<Position>, DexError>;
 
+    /// Amend a resting order in place (`Unsupported` on venues without modify)
+    async fn modify_order(
+        &self,
+        order: OrderRef,
+        req: OrderReq,
+    ) -> Result<OrderStatusResult, DexError> {
+        self.batch_modify(vec![ModifyReq { order, req }])
+            .await?
+            .pop()
+            .ok_or_else(|| DexError::Parse("Missing modify status".into()))
+    }
+
+    /// Amend several resting orders in one request (`Unsupported` on venues without modify)
+    async fn batch_modify(
+        &self,
+        _modifies: Vec<ModifyReq>,
+    ) -> Result<Vec<OrderStatusResult>, DexError> {
+        Err(DexError::Unsupported("order modification"))
+    }
+
     /// Get user's perpetual trading state (requires authentication)
     async fn user_state(&self) -> Result<UserState, DexError>;
 
@@ -105,10 +335,11 @@ pub trait PerpDex: Send + Sync {
     ) -> Result<Vec<UserFill>, DexError>;
 
     /* ---------- streaming ---------- */
+    /// Stream events into `tx` until the returned handle is dropped
     async fn subscribe(
         &self,
         kind: StreamKind,
         coin: Option<&str>,
         tx: mpsc::UnboundedSender<StreamEvent>,
-    ) -> Result<(), DexError>;
+    ) -> Result<SubscriptionHandle, DexError>;
 }
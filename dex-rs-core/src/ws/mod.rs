@@ -21,6 +21,26 @@ pub trait WsConnection: Send + Sync {
 
     /// Close the WebSocket connection
     async fn close(&mut self) -> Result<(), DexError>;
+
+    /// Split into read and write halves that can be used concurrently
+    fn split(self: Box<Self>) -> (Box<dyn WsReader>, Box<dyn WsWriter>);
+}
+
+/// Read half of a split [`WsConnection`]
+#[async_trait]
+pub trait WsReader: Send {
+    /// Read the next message from the WebSocket
+    async fn read_message(&mut self) -> Result<Vec<u8>, DexError>;
+}
+
+/// Write half of a split [`WsConnection`]
+#[async_trait]
+pub trait WsWriter: Send {
+    /// Send a message to the WebSocket
+    async fn send_message(&mut self, data: Bytes) -> Result<(), DexError>;
+
+    /// Close the WebSocket connection
+    async fn close(&mut self) -> Result<(), DexError>;
 }
 
 /* ---------- FastWebSocket impl (Tokio) ---------- */
@@ -2,9 +2,16 @@
 
 use super::*;
 use bytes::Bytes;
-use fastwebsockets::{Frame, OpCode, Payload, WebSocket};
+use fastwebsockets::{Frame, OpCode, Payload, WebSocket, WebSocketRead, WebSocketWrite};
+use hyper::upgrade::Upgraded;
+use hyper_util::rt::tokio::TokioIo;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::{
+    io::{ReadHalf, WriteHalf},
+    sync::Mutex,
+};
+
+type Stream = TokioIo<Upgraded>;
 
 #[derive(Clone)]
 pub struct FastWsTransport;
@@ -82,23 +89,19 @@ impl WsTransport for FastWsTransport {
                 .await
                 .map_err(|e| DexError::Ws(format!("WebSocket handshake failed: {}", e)))?;
 
-            Ok(Box::new(FastWsConnection {
-                ws: Arc::new(Mutex::new(ws)),
-            }))
+            Ok(Box::new(FastWsConnection { ws: Mutex::new(ws) }))
         } else {
             let (ws, _) = fastwebsockets::handshake::client(&executor, req, tcp_stream)
                 .await
                 .map_err(|e| DexError::Ws(format!("WebSocket handshake failed: {}", e)))?;
 
-            Ok(Box::new(FastWsConnection {
-                ws: Arc::new(Mutex::new(ws)),
-            }))
+            Ok(Box::new(FastWsConnection { ws: Mutex::new(ws) }))
         }
     }
 }
 
 pub struct FastWsConnection {
-    ws: Arc<Mutex<WebSocket<hyper_util::rt::tokio::TokioIo<hyper::upgrade::Upgraded>>>>,
+    ws: Mutex<WebSocket<Stream>>,
 }
 
 #[async_trait]
@@ -151,4 +154,83 @@ impl WsConnection for FastWsConnection {
             .await
             .map_err(|e| DexError::Ws(format!("Failed to close connection: {}", e)))
     }
+
+    fn split(self: Box<Self>) -> (Box<dyn WsReader>, Box<dyn WsWriter>) {
+        let (read, write) = self.ws.into_inner().split(tokio::io::split);
+        // Pongs and close replies owed by the reader go out through the shared writer
+        let write = Arc::new(Mutex::new(write));
+        (
+            Box::new(FastWsReader {
+                read,
+                write: write.clone(),
+            }),
+            Box::new(FastWsWriter { write }),
+        )
+    }
+}
+
+type SharedWrite = Arc<Mutex<WebSocketWrite<WriteHalf<Stream>>>>;
+
+pub struct FastWsReader {
+    read: WebSocketRead<ReadHalf<Stream>>,
+    write: SharedWrite,
+}
+
+#[async_trait]
+impl WsReader for FastWsReader {
+    async fn read_message(&mut self) -> Result<Vec<u8>, DexError> {
+        let write = self.write.clone();
+        let mut send_obligated = move |frame| {
+            let write = write.clone();
+            async move { write.lock().await.write_frame(frame).await }
+        };
+        loop {
+            let frame = self
+                .read
+                .read_frame(&mut send_obligated)
+                .await
+                .map_err(|e| DexError::Ws(format!("Failed to read frame: {}", e)))?;
+
+            match frame.opcode {
+                OpCode::Text | OpCode::Binary => {
+                    return Ok(frame.payload.to_vec());
+                }
+                OpCode::Close => {
+                    return Err(DexError::Ws("Connection closed by peer".into()));
+                }
+                // Pings are answered by `read_frame` itself
+                OpCode::Ping | OpCode::Pong => {}
+                OpCode::Continuation => {
+                    return Err(DexError::Ws("Unexpected continuation frame".into()));
+                }
+            }
+        }
+    }
+}
+
+pub struct FastWsWriter {
+    write: SharedWrite,
+}
+
+#[async_trait]
+impl WsWriter for FastWsWriter {
+    async fn send_message(&mut self, data: Bytes) -> Result<(), DexError> {
+        let frame = Frame::text(Payload::Owned(data.to_vec()));
+        self.write
+            .lock()
+            .await
+            .write_frame(frame)
+            .await
+            .map_err(|e| DexError::Ws(format!("Failed to send message: {}", e)))
+    }
+
+    async fn close(&mut self) -> Result<(), DexError> {
+        let frame = Frame::close(1000, b"");
+        self.write
+            .lock()
+            .await
+            .write_frame(frame)
+            .await
+            .map_err(|e| DexError::Ws(format!("Failed to close connection: {}", e)))
+    }
 }
@@ -55,8 +55,11 @@ async fn main() -> DexResult<()> {
     println!("   Price: ${}", order_to_cancel.limit_px);
     println!("   Size: {}", order_to_cancel.sz);
 
-    match hl.cancel(order_id).await {
-        Ok(()) => {
+    match hl.cancel(&order_to_cancel.coin, order_id).await {
+        Ok(CancelStatus::Error(reason)) => {
+            println!("❌ Exchange rejected the cancel: {}", reason);
+        }
+        Ok(CancelStatus::Success) => {
             println!("✅ Order canceled successfully!");
 
             // Wait a moment then verify cancellation
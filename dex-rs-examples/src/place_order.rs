@@ -57,6 +57,7 @@ async fn main() -> DexResult<()> {
         tif: Tif::Gtc,
         reduce_only: false,
         cloid: None,
+        trigger: None,
     };
 
     match hl.place_order(order_req).await {
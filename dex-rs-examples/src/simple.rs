@@ -7,7 +7,7 @@ async fn main() -> DexResult<()> {
     println!("Last trade: {:?}", hl.trades("BTC", 1).await?.pop());
 
     let (tx, mut rx) = mpsc::unbounded_channel();
-    hl.subscribe(StreamKind::Bbo, Some("BTC"), tx).await?;
+    let _bbo = hl.subscribe(StreamKind::Bbo, Some("BTC"), tx).await?;
 
     while let Some(ev) = rx.recv().await {
         if let StreamEvent::Bbo { bid_px, ask_px, .. } = ev {
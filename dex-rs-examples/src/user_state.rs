@@ -114,7 +114,7 @@ async fn main() -> DexResult<()> {
 
             // Show leverage if available
             if let Some(leverage) = &position.leverage {
-                println!("         Leverage: {:.1}x", leverage.into_inner());
+                println!("         Leverage: {}x {:?}", leverage.value, leverage.kind);
             }
 
             // Show return on equity if available
@@ -141,25 +141,8 @@ async fn main() -> DexResult<()> {
     }
 
     // Withdrawal information
-    if !user_state.withdrawals_used.is_empty() {
-        println!("\n💸 Withdrawal Limits:");
-        for withdrawal in &user_state.withdrawals_used {
-            let used: f64 = withdrawal.used.parse().unwrap_or(0.0);
-            let limit: f64 = withdrawal.limit.parse().unwrap_or(0.0);
-            let remaining = limit - used;
-            let utilization = if limit > 0.0 {
-                (used / limit) * 100.0
-            } else {
-                0.0
-            };
-
-            println!(
-                "   Used: ${:.2} / ${:.2} ({:.1}%)",
-                used, limit, utilization
-            );
-            println!("   Remaining: ${:.2}", remaining);
-        }
-    }
+    let withdrawable: f64 = user_state.withdrawable.parse().unwrap_or(0.0);
+    println!("\n💸 Withdrawable: ${:.2}", withdrawable);
 
     // Risk metrics
     println!("\n⚠️ Risk Metrics:");
@@ -42,28 +42,37 @@ async fn main() -> DexResult<()> {
 
     println!("\n📡 Subscribing to streams...");
 
+    // Streams stay subscribed while their handles are alive
+    let mut handles = Vec::new();
+
     // Subscribe to public streams for each coin
     for coin in &coins {
         println!("  📈 Trades for {}", coin);
-        hl.subscribe(StreamKind::Trades, Some(coin), trades_tx.clone())
-            .await?;
+        handles.push(
+            hl.subscribe(StreamKind::Trades, Some(coin), trades_tx.clone())
+                .await?,
+        );
 
         println!("  💹 BBO for {}", coin);
-        hl.subscribe(StreamKind::Bbo, Some(coin), bbo_tx.clone())
-            .await?;
+        handles.push(
+            hl.subscribe(StreamKind::Bbo, Some(coin), bbo_tx.clone())
+                .await?,
+        );
 
         println!("  📖 L2 Book for {}", coin);
-        hl.subscribe(StreamKind::L2Book, Some(coin), l2_tx.clone())
-            .await?;
+        handles.push(
+            hl.subscribe(StreamKind::L2Book, Some(coin), l2_tx.clone())
+                .await?,
+        );
     }
 
     // Subscribe to authenticated streams if available
     if has_auth {
         println!("  📋 Order updates");
-        hl.subscribe(StreamKind::Orders, None, orders_tx).await?;
+        handles.push(hl.subscribe(StreamKind::Orders, None, orders_tx).await?);
 
         println!("  💵 Fill updates");
-        hl.subscribe(StreamKind::Fills, None, fills_tx).await?;
+        handles.push(hl.subscribe(StreamKind::Fills, None, fills_tx).await?);
     }
 
     println!("\n✅ All subscriptions completed successfully!");
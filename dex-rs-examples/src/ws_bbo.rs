@@ -17,7 +17,7 @@ async fn main() -> DexResult<()> {
     println!("Press Ctrl+C to exit\n");
 
     let (tx, mut rx) = mpsc::unbounded_channel();
-    hl.subscribe(StreamKind::Bbo, Some(coin), tx).await?;
+    let _bbo = hl.subscribe(StreamKind::Bbo, Some(coin), tx).await?;
 
     let mut update_count = 0;
     let mut price_history: VecDeque<f64> = VecDeque::with_capacity(100);
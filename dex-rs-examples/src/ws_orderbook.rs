@@ -17,7 +17,7 @@ async fn main() -> DexResult<()> {
     println!("Press Ctrl+C to exit\n");
 
     let (tx, mut rx) = mpsc::unbounded_channel();
-    hl.subscribe(StreamKind::L2Book, Some(coin), tx).await?;
+    let _book = hl.subscribe(StreamKind::L2Book, Some(coin), tx).await?;
 
     let mut update_count = 0;
 
@@ -14,7 +14,7 @@ async fn main() -> DexResult<()> {
     println!("Press Ctrl+C to exit\n");
 
     let (tx, mut rx) = mpsc::unbounded_channel();
-    hl.subscribe(StreamKind::Trades, Some(coin), tx).await?;
+    let _trades = hl.subscribe(StreamKind::Trades, Some(coin), tx).await?;
 
     let mut trade_count = 0;
     let start_time = tokio::time::Instant::now();
@@ -24,8 +24,8 @@ async fn main() -> DexResult<()> {
     let (order_tx, mut order_rx) = mpsc::unbounded_channel();
     let (fill_tx, mut fill_rx) = mpsc::unbounded_channel();
 
-    hl.subscribe(StreamKind::Orders, None, order_tx).await?;
-    hl.subscribe(StreamKind::Fills, None, fill_tx).await?;
+    let _orders = hl.subscribe(StreamKind::Orders, None, order_tx).await?;
+    let _fills = hl.subscribe(StreamKind::Fills, None, fill_tx).await?;
 
     println!("🎯 Listening for order updates and fills...");
     println!("{:=<80}", "");
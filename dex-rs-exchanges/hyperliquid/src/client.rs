@@ -1,21 +1,47 @@
-use std::sync::{
-    atomic::{AtomicU64, Ordering},
-    Arc,
+use serde::Serialize;
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 use tokio::sync::mpsc;
 
 use dex_rs_core::{
     http::{reqwest_impl::ReqwestTransport, Http},
-    traits::{PerpDex, Position, StreamEvent, StreamKind},
+    rt_tokio::TokioRt,
+    runtime::{Sleep, Spawn},
+    traits::{PerpDex, Position, StreamEvent, StreamKind, SubscriptionHandle},
     ws::tokio_fastws::FastWsTransport,
     DexError,
 };
 use dex_rs_types::*;
 
-use crate::{http::HlRest, signer::HlSigner, ws::HlWs};
+use crate::{
+    http::HlRest,
+    registry::{AssetInfo, AssetRegistry},
+    rounding::{normalize_order, normalize_size, round_price},
+    signer::{
+        cloid_to_wire, ApproveAgentAction, ApproveBuilderFeeAction, BatchModifyAction, BuilderWire,
+        CDepositAction, CWithdrawAction, CancelAction, CancelByCloidAction, HlSigner, Modify,
+        OidOrCloid, Order, OrderAction, ScheduleCancelAction, SpotSendAction,
+        SubAccountTransferAction, TokenDelegateAction, TwapCancelAction, TwapOrderAction,
+        UpdateIsolatedMarginAction, UpdateLeverageAction, UsdClassTransferAction, UsdSendAction,
+        UserSignedAction, VaultTransferAction, WithdrawAction,
+    },
+    ws::{HlWs, ReconnectPolicy},
+};
 
 static CUR_NONCE: AtomicU64 = AtomicU64::new(0);
 
+/// Token staked with validators
+const STAKING_TOKEN: &str = "HYPE";
+
+/// Allowed TWAP durations (5 minutes to 24 hours)
+const TWAP_MIN_MINUTES: u32 = 5;
+const TWAP_MAX_MINUTES: u32 = 1440;
+
 fn now_timestamp_ms() -> u64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -23,20 +49,43 @@ fn now_timestamp_ms() -> u64 {
         .as_millis() as u64
 }
 
+/// Strictly increasing nonce that never falls behind the wall clock
 pub(crate) fn next_nonce() -> u64 {
-    let nonce = CUR_NONCE.fetch_add(1, Ordering::Relaxed);
     let now_ms = now_timestamp_ms();
-    // more than 300 seconds behind
-    if nonce + 300000 < now_ms {
-        CUR_NONCE.fetch_max(now_ms, Ordering::Relaxed);
-    }
-    nonce
+    let prev = CUR_NONCE
+        .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |prev| {
+            Some((prev + 1).max(now_ms))
+        })
+        .unwrap_or_default();
+    (prev + 1).max(now_ms)
+}
+
+/// How signed exchange actions reach the exchange
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OrderTransport {
+    /// HTTP `POST` to `/exchange`
+    #[default]
+    Http,
+    /// `post` requests on the shared WebSocket connection, which skip a connection
+    /// setup per request
+    WebSocket,
 }
 
 pub struct Hyperliquid {
-    rest: HlRest,
+    rest: Arc<HlRest>,
     ws: HlWs<FastWsTransport>,
     signer: Option<HlSigner>,
+    /// Account queried for user data when the signer is an agent wallet
+    account_address: Option<String>,
+    /// Vault or sub-account that L1 actions are signed for by default
+    vault_address: Option<String>,
+    heartbeat: Option<HeartbeatGuard>,
+    /// Builder code attached to every order
+    builder: Option<BuilderWire>,
+    registry: AssetRegistry,
+    testnet: bool,
+    strict_rounding: bool,
+    order_transport: OrderTransport,
 }
 
 impl Hyperliquid {
@@ -44,18 +93,458 @@ impl Hyperliquid {
         HyperliquidBuilder::default()
     }
 
-    /// Get asset index for a given coin symbol by fetching from API
-    async fn get_asset_index(&self, coin: &str) -> Result<u32, DexError> {
-        let meta = self.rest.meta(None).await?;
+    /// Sign an L1 action and post it to the exchange endpoint
+    async fn post_action<A: Serialize + Sync>(
+        &self,
+        action: &A,
+        vault_address: Option<&str>,
+    ) -> Result<serde_json::Value, DexError> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or(DexError::Unsupported("signer required"))?;
+        let payload =
+            sign_l1_action(signer, action, next_nonce(), vault_address, !self.testnet).await?;
+        self.exchange(payload).await
+    }
+
+    /// Sign a user-signed action with the wallet key and post it to the exchange endpoint
+    async fn post_user_action<A: UserSignedAction>(
+        &self,
+        action: &A,
+    ) -> Result<serde_json::Value, DexError> {
+        let signer = self
+            .signer
+            .as_ref()
+            .ok_or(DexError::Unsupported("signer required"))?;
+
+        if self
+            .account_address
+            .as_ref()
+            .is_some_and(|account| *account != signer.address_hex())
+        {
+            return Err(DexError::Unsupported(
+                "user-signed actions require the account owner's key",
+            ));
+        }
+
+        let signature = signer.sign_user_signed_action(action).await?;
+        let payload = serde_json::json!({
+            "action": action,
+            "nonce": action.nonce(),
+            "signature": signature,
+        });
+        self.exchange(payload).await
+    }
+
+    /// Post a signed action over the configured order transport
+    async fn exchange(&self, payload: serde_json::Value) -> Result<serde_json::Value, DexError> {
+        match self.order_transport {
+            OrderTransport::Http => self.rest.exchange(payload).await,
+            OrderTransport::WebSocket => self.ws.post_action(payload).await,
+        }
+    }
 
-        // Look for the coin in the universe
-        for item in &meta.universe {
-            if item.name.eq_ignore_ascii_case(coin) {
-                return Ok(item.index);
+    /// Address whose account data is queried: the configured account address,
+    /// the vault being traded, or the signer's own address
+    fn user_address(&self) -> Result<String, DexError> {
+        if let Some(account) = &self.account_address {
+            return Ok(account.clone());
+        }
+        if let Some(vault) = &self.vault_address {
+            return Ok(vault.trim_start_matches("0x").to_string());
+        }
+        self.signer
+            .as_ref()
+            .map(|signer| signer.address_hex())
+            .ok_or(DexError::Unsupported("signer required"))
+    }
+
+    /// Cached asset metadata used to resolve coins to asset ids
+    pub fn registry(&self) -> &AssetRegistry {
+        &self.registry
+    }
+
+    /// Resolve a coin through the registry, refreshing it on a miss
+    async fn asset(&self, coin: &str) -> Result<AssetInfo, DexError> {
+        self.registry.resolve(&self.rest, coin).await
+    }
+
+    /// Build an IOC order priced `slippage` away from the current mid
+    async fn market_order_req(
+        &self,
+        coin: &str,
+        is_buy: bool,
+        sz: Qty,
+        slippage: f64,
+        reduce_only: bool,
+    ) -> Result<OrderReq, DexError> {
+        let asset = self.asset(coin).await?;
+        let mids = self.rest.all_mids(perp_dex(&asset.name)).await?;
+        let mid = mids
+            .mids
+            .get(&asset.name)
+            .ok_or_else(|| DexError::Other(format!("No mid price for {}", coin)))?
+            .parse::<f64>()
+            .map_err(|_| DexError::Parse(format!("Invalid mid price for {}", coin)))?;
+
+        Ok(OrderReq {
+            coin: asset.name.clone(),
+            is_buy,
+            px: price(slippage_price(
+                mid,
+                is_buy,
+                slippage,
+                asset.sz_decimals,
+                asset.is_spot,
+            )),
+            qty: sz,
+            tif: Tif::Ioc,
+            reduce_only,
+            cloid: None,
+            trigger: None,
+        })
+    }
+
+    async fn place_orders_with_vault(
+        &self,
+        reqs: Vec<OrderReq>,
+        grouping: Grouping,
+        vault_address: Option<&str>,
+    ) -> Result<Vec<OrderStatusResult>, DexError> {
+        if self.signer.is_none() {
+            return Err(DexError::Unsupported("signer required"));
+        }
+        if reqs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut orders = Vec::with_capacity(reqs.len());
+        for req in &reqs {
+            let asset = self.asset(&req.coin).await?;
+            orders.push(order_to_wire(&asset, req, self.strict_rounding)?);
+        }
+
+        let action = OrderAction::new(orders, grouping).with_builder(self.builder.clone());
+        let resp = self.post_action(&action, vault_address).await?;
+        parse_order_statuses(&resp, reqs.len())
+    }
+
+    async fn cancel_many_with_vault(
+        &self,
+        reqs: Vec<CancelReq>,
+        vault_address: Option<&str>,
+    ) -> Result<Vec<CancelStatus>, DexError> {
+        if reqs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Hyperliquid cancels by oid and by cloid in separate actions
+        let mut by_oid = Vec::new();
+        let mut by_cloid = Vec::new();
+        for req in &reqs {
+            let asset_id = self.asset(&req.coin).await?.asset_id;
+            match &req.order {
+                OrderRef::Oid(id) => by_oid.push((asset_id, parse_oid(id)?)),
+                OrderRef::Cloid(cloid) => by_cloid.push((asset_id, cloid_to_wire(cloid)?)),
             }
         }
 
-        Err(DexError::Other(format!("Asset not found: {}", coin)))
+        let mut oid_statuses = Vec::new();
+        if !by_oid.is_empty() {
+            let expected = by_oid.len();
+            let resp = self
+                .post_action(&CancelAction::new(by_oid), vault_address)
+                .await?;
+            oid_statuses = parse_cancel_statuses(&resp, expected)?;
+        }
+        let mut cloid_statuses = Vec::new();
+        if !by_cloid.is_empty() {
+            let expected = by_cloid.len();
+            let resp = self
+                .post_action(&CancelByCloidAction::new(by_cloid), vault_address)
+                .await?;
+            cloid_statuses = parse_cancel_statuses(&resp, expected)?;
+        }
+
+        // Restore the caller's ordering
+        let mut oid_statuses = oid_statuses.into_iter();
+        let mut cloid_statuses = cloid_statuses.into_iter();
+        Ok(reqs
+            .iter()
+            .filter_map(|req| match req.order {
+                OrderRef::Oid(_) => oid_statuses.next(),
+                OrderRef::Cloid(_) => cloid_statuses.next(),
+            })
+            .collect())
+    }
+}
+
+/// Sign an L1 action at `nonce` into an exchange request payload
+async fn sign_l1_action<A: Serialize + Sync>(
+    signer: &HlSigner,
+    action: &A,
+    nonce: u64,
+    vault_address: Option<&str>,
+    is_mainnet: bool,
+) -> Result<serde_json::Value, DexError> {
+    let signature = signer
+        .sign_l1_action(action, nonce, vault_address, is_mainnet)
+        .await?;
+    Ok(serde_json::json!({
+        "action": action,
+        "nonce": nonce,
+        "signature": signature,
+        "vaultAddress": vault_address,
+    }))
+}
+
+/// Sign an L1 action with a fresh nonce and post it to the exchange endpoint
+async fn post_l1_action<A: Serialize + Sync>(
+    rest: &HlRest,
+    signer: &HlSigner,
+    action: &A,
+    vault_address: Option<&str>,
+    is_mainnet: bool,
+) -> Result<serde_json::Value, DexError> {
+    let payload = sign_l1_action(signer, action, next_nonce(), vault_address, is_mainnet).await?;
+    rest.exchange(payload).await
+}
+
+/// Stops the dead-man switch heartbeat when dropped
+struct HeartbeatGuard {
+    stop: Arc<AtomicBool>,
+}
+
+impl Drop for HeartbeatGuard {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Check a leverage update against the asset's limits
+fn validate_leverage(asset: &AssetInfo, leverage: u32, is_cross: bool) -> Result<(), DexError> {
+    if asset.is_spot {
+        return Err(DexError::Validation(format!(
+            "{} is a spot pair and has no leverage",
+            asset.name
+        )));
+    }
+    if leverage == 0 || leverage > asset.max_leverage {
+        return Err(DexError::Validation(format!(
+            "leverage {} for {} must be between 1 and {}",
+            leverage, asset.name, asset.max_leverage
+        )));
+    }
+    if is_cross && asset.only_isolated {
+        return Err(DexError::Validation(format!(
+            "{} only supports isolated margin",
+            asset.name
+        )));
+    }
+    Ok(())
+}
+
+/// Resolve a spot token name to the `NAME:tokenId` form used by transfers
+fn spot_token_wire(spot_meta: &SpotMeta, token: &str) -> Result<String, DexError> {
+    spot_meta
+        .tokens
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(token))
+        .map(|t| format!("{}:{}", t.name, t.token_id))
+        .ok_or_else(|| DexError::Other(format!("Token not found: {}", token)))
+}
+
+/// Convert a positive token amount to integer wei using the token's `weiDecimals`
+fn token_wei(spot_meta: &SpotMeta, token: &str, amount: f64) -> Result<u64, DexError> {
+    let meta = spot_meta
+        .tokens
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(token))
+        .ok_or_else(|| DexError::Other(format!("Token not found: {}", token)))?;
+    let wei = (amount * 10f64.powi(meta.wei_decimals as i32)).round();
+    if wei < 1.0 || wei > u64::MAX as f64 {
+        return Err(DexError::Validation(format!(
+            "{} amount {} is not a positive amount at {} wei decimals",
+            meta.name, amount, meta.wei_decimals
+        )));
+    }
+    Ok(wei as u64)
+}
+
+/// Lowercase an address and make sure it carries a `0x` prefix
+fn normalize_address(address: &str) -> String {
+    let address = address.to_lowercase();
+    match address.strip_prefix("0x") {
+        Some(_) => address,
+        None => format!("0x{}", address),
+    }
+}
+
+/// Convert a positive USD transfer amount to micro-USD
+fn usd_amount(usd: f64) -> Result<u64, DexError> {
+    match usd_to_micros(usd) {
+        micros if micros > 0 => Ok(micros as u64),
+        _ => Err(DexError::Validation(format!(
+            "transfer amount {} must be positive",
+            usd
+        ))),
+    }
+}
+
+/// Convert a USD amount to the integer micro-USD units used by margin actions
+fn usd_to_micros(usd: f64) -> i64 {
+    (usd * 1e6).round() as i64
+}
+
+/// Apply slippage to a mid price and round it to a tick the exchange accepts
+fn slippage_price(mid: f64, is_buy: bool, slippage: f64, sz_decimals: u32, is_spot: bool) -> f64 {
+    let px = if is_buy {
+        mid * (1.0 + slippage)
+    } else {
+        mid * (1.0 - slippage)
+    };
+    round_price(px, sz_decimals, is_spot)
+}
+
+/// Builder-deployed perp dex of a `dex:COIN` name; `None` for the default dex and spot
+fn perp_dex(coin: &str) -> Option<&str> {
+    coin.split_once(':').map(|(dex, _)| dex)
+}
+
+fn parse_oid(id: &OrderId) -> Result<u64, DexError> {
+    id.0.parse::<u64>()
+        .map_err(|e| DexError::Parse(format!("Invalid order ID format: {}", e)))
+}
+
+fn order_ref_to_wire(order: &OrderRef) -> Result<OidOrCloid, DexError> {
+    match order {
+        OrderRef::Oid(id) => parse_oid(id).map(OidOrCloid::Oid),
+        OrderRef::Cloid(cloid) => cloid_to_wire(cloid).map(OidOrCloid::Cloid),
+    }
+}
+
+/// Build the wire form of an order, converting its cloid and rounding
+/// price and size to the asset's precision
+fn order_to_wire(asset: &AssetInfo, req: &OrderReq, strict: bool) -> Result<Order, DexError> {
+    let mut req = req.clone();
+    normalize_order(&mut req, asset.sz_decimals, asset.is_spot, strict)?;
+    let cloid = req.cloid.as_deref().map(cloid_to_wire).transpose()?;
+    Ok(Order::from_req(&req, asset.asset_id, cloid.as_deref()))
+}
+
+/// Extract `data.statuses` from an exchange response, checking one status per request
+fn response_statuses(
+    resp: &serde_json::Value,
+    expected: usize,
+) -> Result<&Vec<serde_json::Value>, DexError> {
+    let statuses = resp["data"]["statuses"]
+        .as_array()
+        .ok_or_else(|| DexError::Parse("Missing statuses in exchange response".into()))?;
+    if statuses.len() != expected {
+        return Err(DexError::Parse(format!(
+            "Expected {} statuses, got {}",
+            expected,
+            statuses.len()
+        )));
+    }
+    Ok(statuses)
+}
+
+/// Parse order statuses: `{"resting": ..}`, `{"filled": ..}`, `{"error": "<message>"}`
+/// or the TP/SL `"waitingForFill"` / `"waitingForTrigger"` strings
+fn parse_order_statuses(
+    resp: &serde_json::Value,
+    expected: usize,
+) -> Result<Vec<OrderStatusResult>, DexError> {
+    response_statuses(resp, expected)?
+        .iter()
+        .map(parse_order_status)
+        .collect()
+}
+
+fn parse_order_status(status: &serde_json::Value) -> Result<OrderStatusResult, DexError> {
+    if let Some(oid) = status["resting"]["oid"].as_u64() {
+        return Ok(OrderStatusResult::Resting {
+            oid: OrderId(oid.to_string()),
+        });
+    }
+    if let Some(oid) = status["filled"]["oid"].as_u64() {
+        let total_sz = parse_number(&status["filled"]["totalSz"], "filled size")?;
+        let avg_px = parse_number(&status["filled"]["avgPx"], "filled price")?;
+        return Ok(OrderStatusResult::Filled {
+            oid: OrderId(oid.to_string()),
+            total_sz: qty(total_sz),
+            avg_px: price(avg_px),
+        });
+    }
+    if let Some(err) = status["error"].as_str() {
+        return Ok(OrderStatusResult::Error(err.to_string()));
+    }
+    match status.as_str() {
+        Some("waitingForFill") => Ok(OrderStatusResult::WaitingForFill),
+        Some("waitingForTrigger") => Ok(OrderStatusResult::WaitingForTrigger),
+        _ => Err(DexError::Parse(format!("Unknown order status: {}", status))),
+    }
+}
+
+/// Parse a decimal string field from an exchange response
+fn parse_number(value: &serde_json::Value, what: &str) -> Result<f64, DexError> {
+    value
+        .as_str()
+        .and_then(|v| v.parse::<f64>().ok())
+        .ok_or_else(|| DexError::Parse(format!("Invalid {}", what)))
+}
+
+/// Parse cancel statuses: `"success"` or `{"error": "<message>"}`
+fn parse_cancel_statuses(
+    resp: &serde_json::Value,
+    expected: usize,
+) -> Result<Vec<CancelStatus>, DexError> {
+    response_statuses(resp, expected)?
+        .iter()
+        .map(|status| {
+            if status == "success" {
+                Ok(CancelStatus::Success)
+            } else if let Some(err) = status["error"].as_str() {
+                Ok(CancelStatus::Error(err.to_string()))
+            } else {
+                Err(DexError::Parse(format!(
+                    "Unknown cancel status: {}",
+                    status
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Parse a TWAP placement status: `{"running": {"twapId": N}}` or `{"error": "<message>"}`
+fn parse_twap_status(resp: &serde_json::Value) -> Result<u64, DexError> {
+    let status = &resp["data"]["status"];
+    if let Some(twap_id) = status["running"]["twapId"].as_u64() {
+        Ok(twap_id)
+    } else if let Some(err) = status["error"].as_str() {
+        Err(DexError::Exchange {
+            code: None,
+            msg: err.to_string(),
+        })
+    } else {
+        Err(DexError::Parse(format!("Unknown TWAP status: {}", status)))
+    }
+}
+
+/// Parse a TWAP cancel status: `"success"` or `{"error": "<message>"}`
+fn parse_twap_cancel_status(resp: &serde_json::Value) -> Result<CancelStatus, DexError> {
+    let status = &resp["data"]["status"];
+    if status == "success" {
+        Ok(CancelStatus::Success)
+    } else if let Some(err) = status["error"].as_str() {
+        Ok(CancelStatus::Error(err.to_string()))
+    } else {
+        Err(DexError::Parse(format!(
+            "Unknown TWAP cancel status: {}",
+            status
+        )))
     }
 }
 
@@ -64,6 +553,14 @@ impl Hyperliquid {
 pub struct HyperliquidBuilder {
     testnet: bool,
     wallet_hex: Option<String>,
+    account_address: Option<String>,
+    vault_address: Option<String>,
+    dead_man_switch: Option<Duration>,
+    strict_rounding: bool,
+    registry_refresh: Option<Duration>,
+    builder: Option<BuilderWire>,
+    reconnect_policy: ReconnectPolicy,
+    order_transport: OrderTransport,
 }
 
 impl HyperliquidBuilder {
@@ -79,19 +576,82 @@ impl HyperliquidBuilder {
         let private_key = std::env::var(env_var).expect("env var missing");
         self.private_key(private_key)
     }
+    /// Query this account instead of the signer's address, for trading with an
+    /// agent key approved via [`Hyperliquid::approve_agent`]
+    pub fn account_address(mut self, address: impl Into<String>) -> Self {
+        let address = address.into().to_lowercase();
+        self.account_address = Some(address.trim_start_matches("0x").to_string());
+        self
+    }
+    /// Sign orders and other L1 actions on behalf of this vault or sub-account
+    pub fn vault_address(mut self, address: impl AsRef<str>) -> Self {
+        self.vault_address = Some(normalize_address(address.as_ref()));
+        self
+    }
+    /// Start a dead-man switch heartbeat on connect (see [`Hyperliquid::start_dead_man_switch`])
+    pub fn dead_man_switch(mut self, timeout: Duration) -> Self {
+        self.dead_man_switch = Some(timeout);
+        self
+    }
+    /// Reject orders whose price or size is off-tick instead of rounding them
+    pub fn strict_rounding(mut self) -> Self {
+        self.strict_rounding = true;
+        self
+    }
+    /// Attach a builder code to every order so `builder` earns `fee` tenths of a basis
+    /// point; the user must first approve it with [`Hyperliquid::approve_builder_fee`]
+    pub fn builder_fee(mut self, builder: impl AsRef<str>, fee: u32) -> Self {
+        self.builder = Some(BuilderWire::new(&normalize_address(builder.as_ref()), fee));
+        self
+    }
+    /// Retry behaviour of the WebSocket connection shared by all subscriptions
+    pub fn reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = policy;
+        self
+    }
+    /// Send orders, cancels and other signed actions over HTTP (default) or as
+    /// WebSocket `post` requests
+    pub fn order_transport(mut self, transport: OrderTransport) -> Self {
+        self.order_transport = transport;
+        self
+    }
+    /// Reload asset metadata when it is older than `interval`
+    /// (by default it is only reloaded on an unknown symbol)
+    pub fn registry_refresh_interval(mut self, interval: Duration) -> Self {
+        self.registry_refresh = Some(interval);
+        self
+    }
 
     pub async fn connect(self) -> Result<Hyperliquid, DexError> {
         let tp = Arc::new(ReqwestTransport::new());
         let http = Http::new(tp.clone());
-        let rest = HlRest::new(http, self.testnet);
-        let ws = HlWs::new(FastWsTransport, self.testnet);
+        let rest = Arc::new(HlRest::new(http, self.testnet));
+        let ws =
+            HlWs::new(FastWsTransport, self.testnet).with_reconnect_policy(self.reconnect_policy);
 
         let signer = self
             .wallet_hex
             .map(|pk| HlSigner::from_hex_key(&pk))
             .transpose()?;
-
-        Ok(Hyperliquid { rest, ws, signer })
+        let registry = AssetRegistry::load(&rest, self.registry_refresh).await?;
+
+        let mut hl = Hyperliquid {
+            rest,
+            ws,
+            signer,
+            account_address: self.account_address,
+            vault_address: self.vault_address,
+            heartbeat: None,
+            builder: self.builder,
+            registry,
+            testnet: self.testnet,
+            strict_rounding: self.strict_rounding,
+            order_transport: self.order_transport,
+        };
+        if let Some(timeout) = self.dead_man_switch {
+            hl.start_dead_man_switch(TokioRt, timeout)?;
+        }
+        Ok(hl)
     }
 }
 
@@ -103,7 +663,7 @@ impl PerpDex for Hyperliquid {
     }
 
     async fn orderbook(&self, coin: &str, depth: usize) -> Result<OrderBook, DexError> {
-        let mut ob = self.rest.l2_book(coin).await?;
+        let mut ob = self.rest.l2_book(coin, None).await?;
         ob.bids.truncate(depth);
         ob.asks.truncate(depth);
         Ok(ob)
@@ -132,47 +692,84 @@ impl PerpDex for Hyperliquid {
 
     /* ---- account ---- */
     async fn place_order(&self, mut req: OrderReq) -> Result<OrderResponse, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-
         // Generate cloid if not provided
         let cloid = req.cloid.get_or_insert_with(generate_cloid).clone();
 
-        let nonce = next_nonce();
-        let asset_index = self.get_asset_index(&req.coin).await?;
-        let sig = signer.sign_order(&req, nonce, asset_index, &cloid).await?;
-        let payload = serde_json::json!({ "type": "order", "orders": [req], "grouping": "na", "signature": sig });
-        let resp = self.rest.place_order(payload).await?;
-        let oid = resp["data"]["statuses"][0]["resting"]["oid"]
-            .as_u64()
-            .ok_or_else(|| DexError::Parse("Failed to parse order ID from response".into()))?;
+        let order_id = match self.place_orders(vec![req], Grouping::Na).await?.remove(0) {
+            OrderStatusResult::Resting { oid } | OrderStatusResult::Filled { oid, .. } => oid,
+            OrderStatusResult::Error(msg) => return Err(DexError::Exchange { code: None, msg }),
+            OrderStatusResult::WaitingForFill | OrderStatusResult::WaitingForTrigger => {
+                return Err(DexError::Parse("Order accepted without an order ID".into()))
+            }
+        };
 
         Ok(OrderResponse {
-            order_id: OrderId(oid.to_string()),
+            order_id,
             client_order_id: cloid,
         })
     }
 
-    async fn cancel(&self, id: OrderId) -> Result<(), DexError> {
-        let oid =
-            id.0.parse::<u64>()
-                .map_err(|e| DexError::Parse(format!("Invalid order ID format: {}", e)))?;
-        let payload = serde_json::json!({ "type":"cancel", "cancels": [{"oid": oid}] });
-        self.rest.place_order(payload).await?;
-        Ok(())
+    async fn place_orders(
+        &self,
+        reqs: Vec<OrderReq>,
+        grouping: Grouping,
+    ) -> Result<Vec<OrderStatusResult>, DexError> {
+        self.place_orders_with_vault(reqs, grouping, self.vault_address.as_deref())
+            .await
     }
 
-    async fn positions(&self) -> Result<Vec<Position>, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        let user_state = self
-            .rest
-            .clearinghouse_state(&signer.address_hex(), None)
+    async fn cancel(&self, coin: &str, id: OrderId) -> Result<CancelStatus, DexError> {
+        let asset_id = self.asset(coin).await?.asset_id;
+        let action = CancelAction::new([(asset_id, parse_oid(&id)?)]);
+        let resp = self
+            .post_action(&action, self.vault_address.as_deref())
+            .await?;
+        Ok(parse_cancel_statuses(&resp, 1)?.remove(0))
+    }
+
+    async fn cancel_by_cloid(&self, coin: &str, cloid: &str) -> Result<CancelStatus, DexError> {
+        let asset_id = self.asset(coin).await?.asset_id;
+        let action = CancelByCloidAction::new([(asset_id, cloid_to_wire(cloid)?)]);
+        let resp = self
+            .post_action(&action, self.vault_address.as_deref())
             .await?;
+        Ok(parse_cancel_statuses(&resp, 1)?.remove(0))
+    }
+
+    async fn cancel_many(&self, reqs: Vec<CancelReq>) -> Result<Vec<CancelStatus>, DexError> {
+        self.cancel_many_with_vault(reqs, self.vault_address.as_deref())
+            .await
+    }
+
+    async fn batch_modify(
+        &self,
+        modifies: Vec<ModifyReq>,
+    ) -> Result<Vec<OrderStatusResult>, DexError> {
+        if self.signer.is_none() {
+            return Err(DexError::Unsupported("signer required"));
+        }
+        if modifies.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut wire = Vec::with_capacity(modifies.len());
+        for m in &modifies {
+            let asset = self.asset(&m.req.coin).await?;
+            wire.push(Modify::new(
+                order_ref_to_wire(&m.order)?,
+                order_to_wire(&asset, &m.req, self.strict_rounding)?,
+            ));
+        }
+
+        let resp = self
+            .post_action(&BatchModifyAction::new(wire), self.vault_address.as_deref())
+            .await?;
+        parse_order_statuses(&resp, modifies.len())
+    }
+
+    async fn positions(&self) -> Result<Vec<Position>, DexError> {
+        let user = self.user_address()?;
+        let user_state = self.rest.clearinghouse_state(&user, None).await?;
 
         Ok(user_state
             .asset_positions
@@ -187,29 +784,18 @@ impl PerpDex for Hyperliquid {
     }
 
     async fn user_state(&self) -> Result<UserState, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest
-            .clearinghouse_state(&signer.address_hex(), None)
-            .await
+        let user = self.user_address()?;
+        self.rest.clearinghouse_state(&user, None).await
     }
 
     async fn open_orders(&self) -> Result<Vec<OpenOrder>, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.open_orders(&signer.address_hex(), None).await
+        let user = self.user_address()?;
+        self.rest.open_orders(&user, None).await
     }
 
     async fn user_fills(&self) -> Result<Vec<UserFill>, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.user_fills(&signer.address_hex()).await
+        let user = self.user_address()?;
+        self.rest.user_fills(&user).await
     }
 
     async fn user_fills_by_time(
@@ -217,12 +803,9 @@ impl PerpDex for Hyperliquid {
         start_time: u64,
         end_time: Option<u64>,
     ) -> Result<Vec<UserFill>, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
+        let user = self.user_address()?;
         self.rest
-            .user_fills_by_time(&signer.address_hex(), start_time, end_time)
+            .user_fills_by_time(&user, start_time, end_time)
             .await
     }
 
@@ -232,8 +815,8 @@ impl PerpDex for Hyperliquid {
         kind: StreamKind,
         coin: Option<&str>,
         tx: mpsc::UnboundedSender<StreamEvent>,
-    ) -> Result<(), DexError> {
-        let address_hex = self.signer.as_ref().map(|s| s.address_hex());
+    ) -> Result<SubscriptionHandle, DexError> {
+        let address_hex = self.user_address().ok();
         self.ws
             .subscribe(kind, coin, tx, address_hex.as_deref())
             .await
@@ -256,13 +839,19 @@ impl Hyperliquid {
             .await
     }
 
+    /// Get an order book snapshot with price levels bucketed by `aggregation`
+    pub async fn orderbook_aggregated(
+        &self,
+        coin: &str,
+        aggregation: BookAggregation,
+    ) -> Result<OrderBook, DexError> {
+        self.rest.l2_book(coin, Some(aggregation)).await
+    }
+
     /// Get user's fee summary (requires authentication)
     pub async fn user_fees(&self) -> Result<UserFees, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.user_fees(&signer.address_hex()).await
+        let user = self.user_address()?;
+        self.rest.user_fees(&user).await
     }
 
     /// Get user's funding payment history (requires authentication)
@@ -271,22 +860,20 @@ impl Hyperliquid {
         start_time: u64,
         end_time: Option<u64>,
     ) -> Result<UserFunding, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest
-            .user_funding(&signer.address_hex(), start_time, end_time)
-            .await
+        let user = self.user_address()?;
+        self.rest.user_funding(&user, start_time, end_time).await
     }
 
     /// Query specific order status (requires authentication)
     pub async fn order_status(&self, oid: u64) -> Result<OrderStatus, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.order_status(&signer.address_hex(), oid).await
+        let user = self.user_address()?;
+        self.rest.order_status(&user, oid).await
+    }
+
+    /// Get user's spot token balances (requires authentication)
+    pub async fn spot_balances(&self) -> Result<SpotBalances, DexError> {
+        let user = self.user_address()?;
+        self.rest.spot_clearinghouse_state(&user).await
     }
 
     /// Get spot market metadata
@@ -301,54 +888,474 @@ impl Hyperliquid {
 
     /// Get user's staking summary (requires authentication)
     pub async fn delegator_summary(&self) -> Result<DelegatorSummary, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.delegator_summary(&signer.address_hex()).await
+        let user = self.user_address()?;
+        self.rest.delegator_summary(&user).await
     }
 
     /// Get user's delegation details (requires authentication)
     pub async fn delegations(&self) -> Result<Vec<Delegation>, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.delegations(&signer.address_hex()).await
+        let user = self.user_address()?;
+        self.rest.delegations(&user).await
     }
 
     /// Get user's staking rewards (requires authentication)
     pub async fn delegator_rewards(&self) -> Result<DelegatorRewards, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.delegator_rewards(&signer.address_hex()).await
+        let user = self.user_address()?;
+        self.rest.delegator_rewards(&user).await
     }
 
     /// Get user's referral state (requires authentication)
     pub async fn referral(&self) -> Result<ReferralState, DexError> {
+        let user = self.user_address()?;
+        self.rest.referral(&user).await
+    }
+
+    /// Open a position with an aggressive IOC limit order `slippage` (e.g. 0.05) away from mid
+    pub async fn market_open(
+        &self,
+        coin: &str,
+        is_buy: bool,
+        sz: Qty,
+        slippage: f64,
+    ) -> Result<OrderStatusResult, DexError> {
+        let req = self
+            .market_order_req(coin, is_buy, sz, slippage, false)
+            .await?;
+        Ok(self.place_orders(vec![req], Grouping::Na).await?.remove(0))
+    }
+
+    /// Close the whole position in `coin` with a reduce-only IOC order (requires authentication)
+    pub async fn market_close(
+        &self,
+        coin: &str,
+        slippage: f64,
+    ) -> Result<OrderStatusResult, DexError> {
+        let user = self.user_address()?;
+        let user_state = self.rest.clearinghouse_state(&user, None).await?;
+
+        let szi = user_state
+            .asset_positions
+            .iter()
+            .find(|pos| pos.coin.eq_ignore_ascii_case(coin))
+            .map(|pos| {
+                pos.szi
+                    .parse::<f64>()
+                    .map_err(|_| DexError::Parse("Invalid position size".into()))
+            })
+            .transpose()?
+            .filter(|szi| *szi != 0.0)
+            .ok_or_else(|| DexError::Other(format!("No open position for {}", coin)))?;
+
+        let req = self
+            .market_order_req(coin, szi < 0.0, qty(szi.abs()), slippage, true)
+            .await?;
+        Ok(self.place_orders(vec![req], Grouping::Na).await?.remove(0))
+    }
+
+    /// Set leverage and margin mode for a perp (requires authentication)
+    pub async fn update_leverage(
+        &self,
+        coin: &str,
+        leverage: u32,
+        is_cross: bool,
+    ) -> Result<(), DexError> {
+        let asset = self.asset(coin).await?;
+        validate_leverage(&asset, leverage, is_cross)?;
+        self.post_action(
+            &UpdateLeverageAction::new(asset.asset_id, is_cross, leverage),
+            self.vault_address.as_deref(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Add (positive) or remove (negative) USD margin on an isolated position (requires authentication)
+    pub async fn update_isolated_margin(&self, coin: &str, usd_delta: f64) -> Result<(), DexError> {
+        let asset = self.asset(coin).await?;
+        if asset.is_spot {
+            return Err(DexError::Validation(format!(
+                "{} is a spot pair and has no margin",
+                asset.name
+            )));
+        }
+        let ntli = usd_to_micros(usd_delta);
+        if ntli == 0 {
+            return Err(DexError::Validation("margin delta must be non-zero".into()));
+        }
+        self.post_action(
+            &UpdateIsolatedMarginAction::new(asset.asset_id, ntli),
+            self.vault_address.as_deref(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Cancel all open orders at `time` (ms since epoch, at least 5s ahead), or clear
+    /// a previously scheduled cancel with `None` (requires authentication)
+    pub async fn schedule_cancel(&self, time: Option<u64>) -> Result<(), DexError> {
+        self.post_action(
+            &ScheduleCancelAction::new(time),
+            self.vault_address.as_deref(),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Keep a scheduled cancel `timeout` ahead of now, refreshing it every `timeout / 3`
+    /// on `rt`. If the process dies, open orders are cancelled once the deadline passes.
+    /// The heartbeat stops when the client is dropped or the switch is restarted.
+    /// Beats are always posted over HTTP, whatever the configured [`OrderTransport`].
+    pub fn start_dead_man_switch<R>(&mut self, rt: R, timeout: Duration) -> Result<(), DexError>
+    where
+        R: Spawn + Sleep + Clone,
+    {
         let signer = self
             .signer
-            .as_ref()
+            .clone()
             .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.referral(&signer.address_hex()).await
+        if timeout < Duration::from_secs(5) {
+            return Err(DexError::Validation(
+                "dead-man switch timeout must be at least 5 seconds".into(),
+            ));
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        // Replacing the guard stops any previous heartbeat
+        self.heartbeat = Some(HeartbeatGuard { stop: stop.clone() });
+
+        let rest = self.rest.clone();
+        let vault_address = self.vault_address.clone();
+        let is_mainnet = !self.testnet;
+        let interval = timeout / 3;
+        let sleeper = rt.clone();
+        rt.spawn(async move {
+            while !stop.load(Ordering::Relaxed) {
+                let deadline = now_timestamp_ms() + timeout.as_millis() as u64;
+                let action = ScheduleCancelAction::new(Some(deadline));
+                // A failed beat is retried on the next tick, well before the deadline
+                let _ = post_l1_action(
+                    &rest,
+                    &signer,
+                    &action,
+                    vault_address.as_deref(),
+                    is_mainnet,
+                )
+                .await;
+                sleeper.sleep(interval).await;
+            }
+        });
+        Ok(())
+    }
+
+    /// Stop the dead-man switch heartbeat; the last scheduled cancel stays in place
+    pub fn stop_dead_man_switch(&mut self) {
+        self.heartbeat = None;
+    }
+
+    /// Start an exchange-side TWAP order, returning its TWAP id (requires authentication)
+    pub async fn twap_order(&self, req: TwapReq) -> Result<u64, DexError> {
+        if !(TWAP_MIN_MINUTES..=TWAP_MAX_MINUTES).contains(&req.minutes) {
+            return Err(DexError::Validation(format!(
+                "TWAP duration must be between {} and {} minutes",
+                TWAP_MIN_MINUTES, TWAP_MAX_MINUTES
+            )));
+        }
+        let asset = self.asset(&req.coin).await?;
+        let sz = normalize_size(
+            &asset.name,
+            *req.qty,
+            asset.sz_decimals,
+            self.strict_rounding,
+        )?;
+        let action = TwapOrderAction::new(
+            asset.asset_id,
+            req.is_buy,
+            sz,
+            req.reduce_only,
+            req.minutes,
+            req.randomize,
+        );
+        let resp = self
+            .post_action(&action, self.vault_address.as_deref())
+            .await?;
+        parse_twap_status(&resp)
+    }
+
+    /// Cancel a running TWAP order (requires authentication)
+    pub async fn twap_cancel(&self, coin: &str, twap_id: u64) -> Result<CancelStatus, DexError> {
+        let asset = self.asset(coin).await?;
+        let resp = self
+            .post_action(
+                &TwapCancelAction::new(asset.asset_id, twap_id),
+                self.vault_address.as_deref(),
+            )
+            .await?;
+        parse_twap_cancel_status(&resp)
+    }
+
+    /// Get user's TWAP slice fills (requires authentication)
+    pub async fn user_twap_slice_fills(&self) -> Result<Vec<TwapSliceFill>, DexError> {
+        let user = self.user_address()?;
+        self.rest.user_twap_slice_fills(&user).await
+    }
+
+    /// Get user's TWAP order history (requires authentication)
+    pub async fn twap_history(&self) -> Result<Vec<TwapHistoryEntry>, DexError> {
+        let user = self.user_address()?;
+        self.rest.twap_history(&user).await
+    }
+
+    /// Place orders on behalf of a vault or sub-account, overriding the builder's vault
+    pub async fn place_orders_for_vault(
+        &self,
+        vault_address: &str,
+        reqs: Vec<OrderReq>,
+        grouping: Grouping,
+    ) -> Result<Vec<OrderStatusResult>, DexError> {
+        let vault = normalize_address(vault_address);
+        self.place_orders_with_vault(reqs, grouping, Some(&vault))
+            .await
+    }
+
+    /// Cancel orders on behalf of a vault or sub-account, overriding the builder's vault
+    pub async fn cancel_many_for_vault(
+        &self,
+        vault_address: &str,
+        reqs: Vec<CancelReq>,
+    ) -> Result<Vec<CancelStatus>, DexError> {
+        let vault = normalize_address(vault_address);
+        self.cancel_many_with_vault(reqs, Some(&vault)).await
+    }
+
+    /// Move USD between the master account and a sub-account (requires the master's key)
+    pub async fn sub_account_transfer(
+        &self,
+        sub_account: &str,
+        is_deposit: bool,
+        usd: f64,
+    ) -> Result<(), DexError> {
+        let action = SubAccountTransferAction::new(
+            &normalize_address(sub_account),
+            is_deposit,
+            usd_amount(usd)?,
+        );
+        self.post_action(&action, None).await?;
+        Ok(())
+    }
+
+    /// Deposit USD into or withdraw it from a vault (requires authentication)
+    pub async fn vault_transfer(
+        &self,
+        vault_address: &str,
+        is_deposit: bool,
+        usd: f64,
+    ) -> Result<(), DexError> {
+        let action = VaultTransferAction::new(
+            &normalize_address(vault_address),
+            is_deposit,
+            usd_amount(usd)?,
+        );
+        self.post_action(&action, None).await?;
+        Ok(())
+    }
+
+    /// Authorize an agent wallet to trade for this account (requires the owner's key)
+    pub async fn approve_agent(
+        &self,
+        agent_address: &str,
+        name: Option<&str>,
+    ) -> Result<(), DexError> {
+        let action = ApproveAgentAction::new(agent_address, name, next_nonce(), !self.testnet)?;
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    /// Send USDC from the perp account to another address (requires authentication)
+    pub async fn usd_send(&self, destination: &str, amount: f64) -> Result<(), DexError> {
+        let action = UsdSendAction::new(destination, amount, next_nonce(), !self.testnet);
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    /// Withdraw USDC to an address on Arbitrum (requires authentication)
+    pub async fn withdraw(&self, destination: &str, amount: f64) -> Result<(), DexError> {
+        let action = WithdrawAction::new(destination, amount, next_nonce(), !self.testnet);
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    /// Send a spot token, given by name (`PURR`) or as `NAME:tokenId` (requires authentication)
+    pub async fn spot_send(
+        &self,
+        destination: &str,
+        token: &str,
+        amount: f64,
+    ) -> Result<(), DexError> {
+        let token = if token.contains(':') {
+            token.to_string()
+        } else {
+            let spot_meta = self.rest.spot_meta().await?;
+            spot_token_wire(&spot_meta, token)?
+        };
+        let action = SpotSendAction::new(destination, &token, amount, next_nonce(), !self.testnet);
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    /// Move USDC between the spot and perp wallets (requires authentication)
+    pub async fn usd_class_transfer(&self, amount: f64, to_perp: bool) -> Result<(), DexError> {
+        let action = UsdClassTransferAction::new(
+            amount,
+            to_perp,
+            next_nonce(),
+            self.vault_address.as_deref(),
+            !self.testnet,
+        );
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    /// Approve `builder` to charge up to `max_fee_rate` (e.g. `"0.001%"`) on this
+    /// account's orders (requires authentication)
+    pub async fn approve_builder_fee(
+        &self,
+        builder: &str,
+        max_fee_rate: &str,
+    ) -> Result<(), DexError> {
+        let action =
+            ApproveBuilderFeeAction::new(builder, max_fee_rate, next_nonce(), !self.testnet)?;
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    /// Get the maximum fee (in tenths of a basis point) approved for `builder` (requires authentication)
+    pub async fn max_builder_fee(&self, builder: &str) -> Result<u32, DexError> {
+        let user = self.user_address()?;
+        self.rest
+            .max_builder_fee(&user, &normalize_address(builder))
+            .await
+    }
+
+    /// Stake HYPE with a validator, or unstake it with `is_undelegate` (requires authentication)
+    pub async fn token_delegate(
+        &self,
+        validator: &str,
+        amount: f64,
+        is_undelegate: bool,
+    ) -> Result<(), DexError> {
+        let wei = self.staking_wei(amount).await?;
+        let action =
+            TokenDelegateAction::new(validator, wei, is_undelegate, next_nonce(), !self.testnet)?;
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    /// Move HYPE from the spot balance into the staking balance (requires authentication)
+    pub async fn c_deposit(&self, amount: f64) -> Result<(), DexError> {
+        let wei = self.staking_wei(amount).await?;
+        let action = CDepositAction::new(wei, next_nonce(), !self.testnet);
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    /// Move HYPE from the staking balance back to spot (requires authentication)
+    pub async fn c_withdraw(&self, amount: f64) -> Result<(), DexError> {
+        let wei = self.staking_wei(amount).await?;
+        let action = CWithdrawAction::new(wei, next_nonce(), !self.testnet);
+        self.post_user_action(&action).await?;
+        Ok(())
+    }
+
+    async fn staking_wei(&self, amount: f64) -> Result<u64, DexError> {
+        let spot_meta = self.rest.spot_meta().await?;
+        token_wei(&spot_meta, STAKING_TOKEN, amount)
     }
 
     /// Get user's sub-accounts (requires authentication)
     pub async fn sub_accounts(&self) -> Result<Vec<SubAccount>, DexError> {
-        let signer = self
-            .signer
-            .as_ref()
-            .ok_or(DexError::Unsupported("signer required"))?;
-        self.rest.sub_accounts(&signer.address_hex()).await
+        let user = self.user_address()?;
+        self.rest.sub_accounts(&user).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use dex_rs_types::{price, qty, Tif};
+    use bytes::Bytes;
+    use dex_rs_core::http::HttpTransport;
+    use dex_rs_types::{price, qty, AssetMeta, Tif};
+    use std::sync::Mutex;
+
+    /// Records request bodies and answers each with `respond(body)`
+    struct MockHttp {
+        requests: Mutex<Vec<(String, serde_json::Value)>>,
+        respond: fn(&serde_json::Value) -> serde_json::Value,
+    }
+
+    impl MockHttp {
+        fn new(respond: fn(&serde_json::Value) -> serde_json::Value) -> Arc<Self> {
+            Arc::new(Self {
+                requests: Mutex::new(Vec::new()),
+                respond,
+            })
+        }
+
+        fn requests(&self) -> Vec<(String, serde_json::Value)> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HttpTransport for MockHttp {
+        async fn call(
+            &self,
+            req: http::Request<Vec<u8>>,
+        ) -> Result<http::Response<Bytes>, DexError> {
+            let body: serde_json::Value = serde_json::from_slice(req.body())?;
+            let resp = (self.respond)(&body);
+            self.requests
+                .lock()
+                .unwrap()
+                .push((req.uri().path().to_string(), body));
+            Ok(http::Response::new(Bytes::from(resp.to_string())))
+        }
+    }
+
+    fn perp_meta(names: &[(&str, u32)]) -> UniverseMeta {
+        UniverseMeta {
+            assets: names
+                .iter()
+                .map(|&(name, sz_decimals)| AssetMeta {
+                    name: name.into(),
+                    sz_decimals,
+                    max_leverage: 50,
+                    only_isolated: false,
+                })
+                .collect(),
+            universe: vec![],
+        }
+    }
+
+    /// Mainnet client over `http`, with BTC and ETH perps and an `xyz` builder dex
+    fn mock_client(http: Arc<MockHttp>, signer: Option<HlSigner>) -> Hyperliquid {
+        Hyperliquid {
+            rest: Arc::new(HlRest::new(Http::new(http), false)),
+            ws: HlWs::new(FastWsTransport, false),
+            signer,
+            account_address: None,
+            vault_address: None,
+            heartbeat: None,
+            builder: None,
+            registry: AssetRegistry::from_meta(
+                &perp_meta(&[("BTC", 5), ("ETH", 4)]),
+                None,
+                &[perp_meta(&[("xyz:AAA", 2)])],
+            ),
+            testnet: false,
+            strict_rounding: false,
+            order_transport: OrderTransport::Http,
+        }
+    }
 
     #[test]
     fn test_builder_pattern() {
@@ -356,208 +1363,534 @@ mod tests {
 
         // Test testnet flag
         let testnet_builder = builder.testnet();
-        assert_eq!(testnet_builder.testnet, true);
+        assert!(testnet_builder.testnet);
 
         // Test wallet hex
         let wallet_builder = HyperliquidBuilder::default()
             .private_key("0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef");
         assert!(wallet_builder.wallet_hex.is_some());
+
+        // Agent mode: account address is normalized like the signer's address
+        let agent_builder = HyperliquidBuilder::default()
+            .account_address("0x5E9EE1089755C3435139848E47E6635505D5A13A");
+        assert_eq!(
+            agent_builder.account_address.as_deref(),
+            Some("5e9ee1089755c3435139848e47e6635505d5a13a")
+        );
+
+        let vault_builder =
+            HyperliquidBuilder::default().vault_address("1D9470D4B963F552E6F671A81619D395877BF409");
+        assert_eq!(
+            vault_builder.vault_address.as_deref(),
+            Some("0x1d9470d4b963f552e6f671a81619d395877bf409")
+        );
+
+        let fee_builder = HyperliquidBuilder::default()
+            .builder_fee("1D9470D4B963F552E6F671A81619D395877BF409", 10);
+        assert_eq!(
+            fee_builder.builder,
+            Some(BuilderWire::new(
+                "0x1d9470d4b963f552e6f671a81619d395877bf409",
+                10
+            ))
+        );
+
+        let policy_builder =
+            HyperliquidBuilder::default().reconnect_policy(ReconnectPolicy::infinite());
+        assert_eq!(policy_builder.reconnect_policy.max_retries, None);
+
+        assert_eq!(
+            HyperliquidBuilder::default().order_transport,
+            OrderTransport::Http
+        );
+        let ws_builder = HyperliquidBuilder::default().order_transport(OrderTransport::WebSocket);
+        assert_eq!(ws_builder.order_transport, OrderTransport::WebSocket);
+    }
+
+    #[test]
+    fn test_next_nonce_unique_across_threads() {
+        let threads: Vec<_> = (0..8)
+            .map(|_| std::thread::spawn(|| (0..1000).map(|_| next_nonce()).collect::<Vec<_>>()))
+            .collect();
+        let mut nonces: Vec<u64> = threads
+            .into_iter()
+            .flat_map(|t| t.join().unwrap())
+            .collect();
+        let total = nonces.len();
+        nonces.sort_unstable();
+        nonces.dedup();
+        assert_eq!(nonces.len(), total);
+        assert!(nonces[0] + 300_000 >= now_timestamp_ms());
+    }
+
+    #[test]
+    fn test_heartbeat_guard_stops_on_drop() {
+        let stop = Arc::new(AtomicBool::new(false));
+        let guard = HeartbeatGuard { stop: stop.clone() };
+        assert!(!stop.load(Ordering::Relaxed));
+        drop(guard);
+        assert!(stop.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_usd_amount() {
+        assert_eq!(usd_amount(1.5).unwrap(), 1_500_000);
+        assert!(matches!(usd_amount(0.0), Err(DexError::Validation(_))));
+        assert!(matches!(usd_amount(-1.0), Err(DexError::Validation(_))));
+    }
+
+    #[test]
+    fn test_token_wei() {
+        let spot_meta: SpotMeta = serde_json::from_value(serde_json::json!({
+            "tokens": [{
+                "name": "HYPE",
+                "szDecimals": 2,
+                "weiDecimals": 8,
+                "index": 150,
+                "tokenId": "0x0d01dc56dcaaca66ad901c959b4011ec",
+                "isCanonical": false
+            }],
+            "universe": []
+        }))
+        .unwrap();
+
+        assert_eq!(token_wei(&spot_meta, "HYPE", 1.5).unwrap(), 150_000_000);
+        assert_eq!(token_wei(&spot_meta, "hype", 0.00000001).unwrap(), 1);
+        assert!(matches!(
+            token_wei(&spot_meta, "HYPE", 0.000000001),
+            Err(DexError::Validation(_))
+        ));
+        assert!(matches!(
+            token_wei(&spot_meta, "HYPE", -1.0),
+            Err(DexError::Validation(_))
+        ));
+        assert!(token_wei(&spot_meta, "PURR", 1.0).is_err());
     }
 
     #[test]
     fn test_builder_defaults() {
         let builder = HyperliquidBuilder::default();
-        assert_eq!(builder.testnet, false);
+        assert!(!builder.testnet);
         assert!(builder.wallet_hex.is_none());
+        assert!(!builder.strict_rounding);
+        assert!(builder.registry_refresh.is_none());
+        assert!(builder.account_address.is_none());
+        assert!(builder.vault_address.is_none());
+        assert!(builder.dead_man_switch.is_none());
+        assert!(builder.builder.is_none());
+        assert_eq!(builder.reconnect_policy, ReconnectPolicy::default());
     }
 
-    #[test]
-    fn test_order_payload_construction() {
-        use serde_json::json;
-
-        let order_req = OrderReq {
-            coin: "BTC".to_string(),
+    /// ETH order from the reference Python SDK signing tests
+    fn sdk_eth_order() -> OrderReq {
+        OrderReq {
+            coin: "ETH".to_string(),
             is_buy: true,
-            px: price(50000.0),
-            qty: qty(0.001),
+            px: price(100.0),
+            qty: qty(100.0),
             tif: Tif::Gtc,
             reduce_only: false,
             cloid: None,
-        };
+            trigger: None,
+        }
+    }
 
-        // Test the payload structure that would be sent
-        let expected_payload = json!({
-            "type": "order",
-            "orders": [order_req],
-            "grouping": "na",
-            "signature": "mock_signature"
+    #[tokio::test]
+    async fn test_order_payload_matches_reference() {
+        let signer = HlSigner::from_hex_key(
+            "0x0123456789012345678901234567890123456789012345678901234567890123",
+        )
+        .unwrap();
+        let hl = mock_client(MockHttp::new(|_| serde_json::Value::Null), None);
+        let asset = hl.asset("ETH").await.unwrap();
+        let order = order_to_wire(&asset, &sdk_eth_order(), false).unwrap();
+        let action = OrderAction::new(vec![order], Grouping::Na).with_builder(None);
+
+        let payload = sign_l1_action(&signer, &action, 0, None, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "action": {
+                    "type": "order",
+                    "orders": [{
+                        "a": 1,
+                        "b": true,
+                        "p": "100",
+                        "s": "100",
+                        "r": false,
+                        "t": {"limit": {"tif": "Gtc"}}
+                    }],
+                    "grouping": "na"
+                },
+                "nonce": 0,
+                "signature": {
+                    "r": "0xd65369825a9df5d80099e513cce430311d7d26ddf477f5b3a33d2806b100d78e",
+                    "s": "0x2b54116ff64054968aa237c20ca9ff68000f977c93289157748a3162b6ea940e",
+                    "v": 28
+                },
+                "vaultAddress": null
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_place_orders_posts_signed_action() {
+        let signer = HlSigner::from_hex_key(
+            "0x0123456789012345678901234567890123456789012345678901234567890123",
+        )
+        .unwrap();
+        let http = MockHttp::new(|_| {
+            serde_json::json!({
+                "status": "ok",
+                "response": {"type": "order", "data": {"statuses": [{"resting": {"oid": 1}}]}}
+            })
         });
+        let hl = mock_client(http.clone(), Some(signer.clone()));
+
+        let statuses = hl
+            .place_orders_with_vault(vec![sdk_eth_order()], Grouping::Na, None)
+            .await
+            .unwrap();
+        assert_eq!(statuses.len(), 1);
 
-        assert_eq!(expected_payload["type"], "order");
-        assert_eq!(expected_payload["grouping"], "na");
-        assert!(expected_payload["orders"].is_array());
-        assert!(expected_payload["signature"].is_string());
+        let requests = http.requests();
+        assert_eq!(requests.len(), 1);
+        let (path, body) = &requests[0];
+        assert_eq!(path, "/exchange");
+        assert_eq!(
+            body["action"],
+            serde_json::json!({
+                "type": "order",
+                "orders": [{
+                    "a": 1,
+                    "b": true,
+                    "p": "100",
+                    "s": "100",
+                    "r": false,
+                    "t": {"limit": {"tif": "Gtc"}}
+                }],
+                "grouping": "na"
+            })
+        );
+        assert!(body["vaultAddress"].is_null());
+
+        // The posted signature covers the order action at the posted nonce
+        let asset = hl.asset("ETH").await.unwrap();
+        let order = order_to_wire(&asset, &sdk_eth_order(), false).unwrap();
+        let action = OrderAction::new(vec![order], Grouping::Na).with_builder(None);
+        let nonce = body["nonce"].as_u64().unwrap();
+        let expected = sign_l1_action(&signer, &action, nonce, None, true)
+            .await
+            .unwrap();
+        assert_eq!(*body, expected);
     }
 
     #[test]
     fn test_cancel_payload_construction() {
-        use serde_json::json;
-
         let order_id = OrderId("12345".to_string());
+        let action = CancelAction::new([(0, parse_oid(&order_id).unwrap())]);
+        let payload = serde_json::to_value(&action).unwrap();
 
-        let expected_payload = json!({
-            "type": "cancel",
-            "cancels": [{"oid": order_id.0.parse::<u64>().unwrap()}]
+        assert_eq!(payload["type"], "cancel");
+        assert!(payload["cancels"].is_array());
+
+        let cancels = payload["cancels"].as_array().unwrap();
+        assert_eq!(cancels.len(), 1);
+        assert_eq!(cancels[0]["a"], 0);
+        assert_eq!(cancels[0]["o"], 12345);
+
+        assert!(parse_oid(&OrderId("not_a_number".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_order_status_parsing() {
+        use serde_json::json;
+
+        let resp = json!({
+            "type": "order",
+            "data": {
+                "statuses": [
+                    {"resting": {"oid": 77738308u64}},
+                    {"filled": {"totalSz": "0.02", "avgPx": "1891.4", "oid": 77747314u64}},
+                    {"error": "Order must have minimum value of $10."}
+                ]
+            }
         });
 
-        assert_eq!(expected_payload["type"], "cancel");
-        assert!(expected_payload["cancels"].is_array());
+        let statuses = parse_order_statuses(&resp, 3).unwrap();
+        assert_eq!(
+            statuses[0],
+            OrderStatusResult::Resting {
+                oid: OrderId("77738308".into())
+            }
+        );
+        assert_eq!(
+            statuses[1],
+            OrderStatusResult::Filled {
+                oid: OrderId("77747314".into()),
+                total_sz: qty(0.02),
+                avg_px: price(1891.4),
+            }
+        );
+        assert_eq!(
+            statuses[2],
+            OrderStatusResult::Error("Order must have minimum value of $10.".into())
+        );
 
-        let cancels = expected_payload["cancels"].as_array().unwrap();
-        assert_eq!(cancels.len(), 1);
-        assert_eq!(cancels[0]["oid"], 12345);
+        let tpsl = json!({"data": {"statuses": ["waitingForFill", "waitingForTrigger"]}});
+        assert_eq!(
+            parse_order_statuses(&tpsl, 2).unwrap(),
+            vec![
+                OrderStatusResult::WaitingForFill,
+                OrderStatusResult::WaitingForTrigger
+            ]
+        );
+
+        let unknown = json!({"data": {"statuses": [{"mystery": {}}]}});
+        assert!(parse_order_statuses(&unknown, 1).is_err());
     }
 
     #[test]
-    fn test_orderbook_depth_limiting() {
-        use dex_rs_types::OrderBookLevel;
-
-        // Create a mock orderbook with more levels than requested
-        let mut orderbook = OrderBook {
-            coin: "BTC".to_string(),
-            ts: 1234567890,
-            bids: vec![
-                OrderBookLevel {
-                    price: price(50000.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-                OrderBookLevel {
-                    price: price(49999.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-                OrderBookLevel {
-                    price: price(49998.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-                OrderBookLevel {
-                    price: price(49997.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-                OrderBookLevel {
-                    price: price(49996.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-            ],
-            asks: vec![
-                OrderBookLevel {
-                    price: price(50001.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-                OrderBookLevel {
-                    price: price(50002.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-                OrderBookLevel {
-                    price: price(50003.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-                OrderBookLevel {
-                    price: price(50004.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-                OrderBookLevel {
-                    price: price(50005.0),
-                    qty: qty(1.0),
-                    n: 0,
-                },
-            ],
+    fn test_spot_order_wire() {
+        let purr = AssetInfo {
+            name: "PURR/USDC".into(),
+            asset_id: 10_000,
+            sz_decimals: 0,
+            max_leverage: 0,
+            only_isolated: false,
+            is_spot: true,
+        };
+        let req = OrderReq {
+            coin: "PURR/USDC".into(),
+            is_buy: true,
+            px: price(0.123456789),
+            qty: qty(100.4),
+            tif: Tif::Alo,
+            reduce_only: false,
+            cloid: None,
+            trigger: None,
         };
 
-        // Test depth limiting (simulating what would happen in orderbook method)
-        let depth = 3;
-        orderbook.bids.truncate(depth);
-        orderbook.asks.truncate(depth);
+        let order = serde_json::to_value(order_to_wire(&purr, &req, false).unwrap()).unwrap();
+        assert_eq!(order["a"], 10_000);
+        // Spot prices keep up to 8 - szDecimals decimals (5 significant figures)
+        assert_eq!(order["p"], "0.12346");
+        assert_eq!(order["s"], "100");
+        assert_eq!(order["t"]["limit"]["tif"], "Alo");
+    }
 
-        assert_eq!(orderbook.bids.len(), 3);
-        assert_eq!(orderbook.asks.len(), 3);
+    #[test]
+    fn test_leverage_validation() {
+        let mut asset = AssetInfo {
+            name: "ETH".into(),
+            asset_id: 1,
+            sz_decimals: 4,
+            max_leverage: 25,
+            only_isolated: false,
+            is_spot: false,
+        };
+        assert!(validate_leverage(&asset, 10, true).is_ok());
+        assert!(validate_leverage(&asset, 25, false).is_ok());
+        assert!(matches!(
+            validate_leverage(&asset, 26, true),
+            Err(DexError::Validation(_))
+        ));
+        assert!(matches!(
+            validate_leverage(&asset, 0, true),
+            Err(DexError::Validation(_))
+        ));
+
+        asset.only_isolated = true;
+        assert!(validate_leverage(&asset, 10, false).is_ok());
+        assert!(matches!(
+            validate_leverage(&asset, 10, true),
+            Err(DexError::Validation(_))
+        ));
+
+        assert_eq!(usd_to_micros(2.5), 2_500_000);
+        assert_eq!(usd_to_micros(-0.1), -100_000);
     }
 
     #[test]
-    fn test_address_extraction() {
-        // Test the pattern used in subscribe method
-        struct MockSigner {
-            address: String,
-        }
+    fn test_cancel_status_parsing() {
+        use serde_json::json;
 
-        impl MockSigner {
-            fn address_hex(&self) -> String {
-                self.address.clone()
+        let resp = json!({
+            "type": "cancel",
+            "data": {
+                "statuses": [
+                    "success",
+                    {"error": "Order was never placed, already canceled, or filled."}
+                ]
             }
-        }
-
-        let signer = Some(MockSigner {
-            address: "1234567890abcdef1234567890abcdef12345678".to_string(),
         });
 
-        // Test the pattern: signer.as_ref().map(|s| s.address_hex())
-        let address_hex = signer.as_ref().map(|s| s.address_hex());
-        let address_str = address_hex.as_deref();
-
+        let statuses = parse_cancel_statuses(&resp, 2).unwrap();
+        assert_eq!(statuses[0], CancelStatus::Success);
         assert_eq!(
-            address_str,
-            Some("1234567890abcdef1234567890abcdef12345678")
+            statuses[1],
+            CancelStatus::Error("Order was never placed, already canceled, or filled.".into())
         );
+
+        // A status count mismatch is a parse error
+        assert!(parse_cancel_statuses(&resp, 3).is_err());
     }
 
     #[test]
-    fn test_error_handling() {
-        // Test unsupported operations
-        use dex_rs_core::DexError;
-
-        let unsupported_error = DexError::Unsupported("test feature");
-        match unsupported_error {
-            DexError::Unsupported(msg) => assert_eq!(msg, "test feature"),
-            _ => panic!("Expected Unsupported error"),
-        }
+    fn test_twap_status_parsing() {
+        use serde_json::json;
 
-        // Test signer required error
-        let signer_error = DexError::Unsupported("signer required");
-        match signer_error {
-            DexError::Unsupported(msg) => assert_eq!(msg, "signer required"),
-            _ => panic!("Expected signer required error"),
-        }
+        let resp =
+            json!({"type": "twapOrder", "data": {"status": {"running": {"twapId": 77738308}}}});
+        assert_eq!(parse_twap_status(&resp).unwrap(), 77738308);
+
+        let resp =
+            json!({"type": "twapOrder", "data": {"status": {"error": "Invalid TWAP duration"}}});
+        assert!(matches!(
+            parse_twap_status(&resp),
+            Err(DexError::Exchange { msg, .. }) if msg == "Invalid TWAP duration"
+        ));
+
+        let resp = json!({"type": "twapCancel", "data": {"status": "success"}});
+        assert_eq!(
+            parse_twap_cancel_status(&resp).unwrap(),
+            CancelStatus::Success
+        );
+        let resp =
+            json!({"type": "twapCancel", "data": {"status": {"error": "TWAP was never placed"}}});
+        assert_eq!(
+            parse_twap_cancel_status(&resp).unwrap(),
+            CancelStatus::Error("TWAP was never placed".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_market_order_on_builder_dex() {
+        let http = MockHttp::new(|body| match body["dex"].as_str() {
+            Some("xyz") => serde_json::json!({"xyz:AAA": "10.0"}),
+            _ => serde_json::json!({"BTC": "65000.0"}),
+        });
+        let hl = mock_client(http.clone(), None);
+
+        let req = hl
+            .market_order_req("xyz:AAA", true, qty(1.0), 0.05, false)
+            .await
+            .unwrap();
+        assert_eq!(req.coin, "xyz:AAA");
+        assert_eq!(*req.px, slippage_price(10.0, true, 0.05, 2, false));
+        assert_eq!(
+            http.requests(),
+            vec![(
+                "/info".to_string(),
+                serde_json::json!({"type": "allMids", "dex": "xyz"})
+            )]
+        );
+
+        // Default dex coins query the default mids
+        hl.market_order_req("BTC", false, qty(1.0), 0.05, false)
+            .await
+            .unwrap();
+        assert_eq!(http.requests()[1].1, serde_json::json!({"type": "allMids"}));
+        assert_eq!(perp_dex("@107"), None);
     }
 
     #[test]
-    fn test_order_id_parsing() {
-        // Test parsing order ID from response
-        use serde_json::json;
+    fn test_slippage_price_rounding() {
+        // 5 significant figures, then at most 6 - szDecimals decimals
+        assert_eq!(slippage_price(30_000.0, true, 0.05, 5, false), 31_500.0);
+        assert_eq!(slippage_price(30_123.0, false, 0.05, 5, false), 28_617.0);
+        assert_eq!(slippage_price(2_345.67, true, 0.01, 4, false), 2_369.1);
+        assert_eq!(slippage_price(1.234567, true, 0.0, 2, false), 1.2346);
+        assert_eq!(slippage_price(0.0123456, false, 0.0, 0, false), 0.012346);
+        assert_eq!(slippage_price(0.0123456, false, 0.0, 3, false), 0.012);
+
+        // Spot allows 8 - szDecimals decimals
+        assert_eq!(slippage_price(0.0123456, false, 0.0, 3, true), 0.01235);
+    }
 
-        let mock_response = json!({
-            "data": {
-                "statuses": [{
-                    "resting": {
-                        "oid": 12345u64
-                    }
-                }]
-            }
+    #[tokio::test]
+    async fn test_orderbook_depth_limiting() {
+        let http = MockHttp::new(|_| {
+            let side = |start: u32, step: i32| {
+                (0..5)
+                    .map(|i| {
+                        let px = start as i32 + step * i;
+                        serde_json::json!({"px": px.to_string(), "sz": "1.0", "n": 1})
+                    })
+                    .collect::<Vec<_>>()
+            };
+            serde_json::json!({
+                "coin": "BTC",
+                "time": 1234567890u64,
+                "levels": [side(50000, -1), side(50001, 1)]
+            })
         });
+        let hl = mock_client(http, None);
 
-        let oid = mock_response["data"]["statuses"][0]["resting"]["oid"]
-            .as_u64()
-            .unwrap();
-        let order_id = OrderId(oid.to_string());
+        let book = hl.orderbook("BTC", 3).await.unwrap();
+        assert_eq!(book.bids.len(), 3);
+        assert_eq!(book.asks.len(), 3);
+        assert_eq!(*book.bids[0].price, 50000.0);
+        assert_eq!(*book.asks[2].price, 50003.0);
+    }
+
+    #[test]
+    fn test_user_address_precedence() {
+        let signer = HlSigner::from_hex_key(
+            "0x0123456789012345678901234567890123456789012345678901234567890123",
+        )
+        .unwrap();
+        let mut hl = mock_client(MockHttp::new(|_| serde_json::Value::Null), None);
+        assert!(matches!(
+            hl.user_address(),
+            Err(DexError::Unsupported("signer required"))
+        ));
+
+        hl.signer = Some(signer.clone());
+        assert_eq!(hl.user_address().unwrap(), signer.address_hex());
+
+        hl.vault_address = Some("0xabc".into());
+        assert_eq!(hl.user_address().unwrap(), "abc");
+
+        hl.account_address = Some("def".into());
+        assert_eq!(hl.user_address().unwrap(), "def");
+    }
+
+    #[tokio::test]
+    async fn test_trading_requires_signer() {
+        let http = MockHttp::new(|_| serde_json::Value::Null);
+        let hl = mock_client(http.clone(), None);
 
-        assert_eq!(order_id.0, "12345");
+        let err = hl.place_order(sdk_eth_order()).await.unwrap_err();
+        assert!(matches!(err, DexError::Unsupported("signer required")));
+        let err = hl.cancel("ETH", OrderId("1".into())).await.unwrap_err();
+        assert!(matches!(err, DexError::Unsupported("signer required")));
+        assert!(http.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_place_order_returns_resting_oid() {
+        let signer = HlSigner::from_hex_key(
+            "0x0123456789012345678901234567890123456789012345678901234567890123",
+        )
+        .unwrap();
+        let http = MockHttp::new(|_| {
+            serde_json::json!({
+                "status": "ok",
+                "response": {"type": "order", "data": {"statuses": [{"resting": {"oid": 12345u64}}]}}
+            })
+        });
+        let hl = mock_client(http.clone(), Some(signer));
+
+        let resp = hl.place_order(sdk_eth_order()).await.unwrap();
+        assert_eq!(resp.order_id, OrderId("12345".into()));
+        // A cloid is generated and sent with the order
+        assert_eq!(
+            http.requests()[0].1["action"]["orders"][0]["c"],
+            serde_json::json!(cloid_to_wire(&resp.client_order_id).unwrap())
+        );
     }
 
     #[test]
@@ -567,8 +1900,20 @@ mod tests {
             StreamKind::Trades,
             StreamKind::Bbo,
             StreamKind::L2Book,
+            StreamKind::L2BookAggregated(BookAggregation::sig_figs(3)),
             StreamKind::Orders,
             StreamKind::Fills,
+            StreamKind::TwapSliceFills,
+            StreamKind::AllMids,
+            StreamKind::Candle(CandleInterval::Min1),
+            StreamKind::Notification,
+            StreamKind::WebData2,
+            StreamKind::UserEvents,
+            StreamKind::UserFundings,
+            StreamKind::UserNonFundingLedgerUpdates,
+            StreamKind::ActiveAssetCtx,
+            StreamKind::ActiveAssetData,
+            StreamKind::UserTwapHistory,
         ];
 
         // Each should map to a specific subscription type
@@ -576,9 +1921,20 @@ mod tests {
             let subscription_type = match kind {
                 StreamKind::Bbo => "bbo",
                 StreamKind::Trades => "trades",
-                StreamKind::L2Book => "l2Book",
+                StreamKind::L2Book | StreamKind::L2BookAggregated(_) => "l2Book",
                 StreamKind::Orders => "orderUpdates",
                 StreamKind::Fills => "userFills",
+                StreamKind::TwapSliceFills => "userTwapSliceFills",
+                StreamKind::AllMids => "allMids",
+                StreamKind::Candle(_) => "candle",
+                StreamKind::Notification => "notification",
+                StreamKind::WebData2 => "webData2",
+                StreamKind::UserEvents => "userEvents",
+                StreamKind::UserFundings => "userFundings",
+                StreamKind::UserNonFundingLedgerUpdates => "userNonFundingLedgerUpdates",
+                StreamKind::ActiveAssetCtx => "activeAssetCtx",
+                StreamKind::ActiveAssetData => "activeAssetData",
+                StreamKind::UserTwapHistory => "userTwapHistory",
             };
 
             assert!(!subscription_type.is_empty());
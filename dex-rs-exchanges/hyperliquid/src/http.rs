@@ -67,12 +67,19 @@ impl HlRest {
     }
 
     /* ----- order-book snapshot ----- */
-    pub async fn l2_book(&self, coin: &str) -> Result<OrderBook, DexError> {
+    /// Order book snapshot, at full precision unless `aggregation` is given
+    pub async fn l2_book(
+        &self,
+        coin: &str,
+        aggregation: Option<BookAggregation>,
+    ) -> Result<OrderBook, DexError> {
         #[derive(Serialize)]
         struct Body<'a> {
             #[serde(rename = "type")]
             kind: &'static str,
             coin: &'a str,
+            #[serde(flatten)]
+            aggregation: Option<BookAggregation>,
         }
         #[derive(Deserialize)]
         struct Level {
@@ -86,10 +93,14 @@ impl HlRest {
             time: u64,
         }
 
+        if let Some(aggregation) = &aggregation {
+            check_book_aggregation(aggregation)?;
+        }
         let url = format!("{}/info", self.base);
         let body = Body {
             kind: "l2Book",
             coin,
+            aggregation,
         };
         let raw: Raw = self.http.post_json(&url, &body).await?;
 
@@ -137,15 +148,16 @@ impl HlRest {
         })
     }
 
-    /* ----- place order ----- */
-    pub async fn place_order(
+    /* ----- exchange actions ----- */
+
+    /// Post a signed action to `/exchange` and return the `response` body
+    pub async fn exchange(
         &self,
         payload: serde_json::Value,
     ) -> Result<serde_json::Value, DexError> {
         let url = format!("{}/exchange", self.base);
-        self.http
-            .post_json::<_, serde_json::Value>(&url, &payload)
-            .await
+        let resp: serde_json::Value = self.http.post_json(&url, &payload).await?;
+        exchange_response(resp)
     }
 
     /* ----- User Account & Trading Data Endpoints ----- */
@@ -171,14 +183,12 @@ impl HlRest {
             user,
             dex,
         };
-        self.http.post_json(&url, &body).await
+        let raw: RawClearinghouseState = self.http.post_json(&url, &body).await?;
+        raw.into_user_state()
     }
 
     /// Get user's spot trading state
-    pub async fn spot_clearinghouse_state(
-        &self,
-        user: &str,
-    ) -> Result<serde_json::Value, DexError> {
+    pub async fn spot_clearinghouse_state(&self, user: &str) -> Result<SpotBalances, DexError> {
         #[derive(Serialize)]
         struct Body<'a> {
             #[serde(rename = "type")]
@@ -287,6 +297,59 @@ impl HlRest {
         self.http.post_json(&url, &body).await
     }
 
+    /// Get the maximum builder fee (in tenths of a basis point) the user has approved for `builder`
+    pub async fn max_builder_fee(&self, user: &str, builder: &str) -> Result<u32, DexError> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            #[serde(rename = "type")]
+            kind: &'static str,
+            user: &'a str,
+            builder: &'a str,
+        }
+
+        let url = format!("{}/info", self.base);
+        let body = Body {
+            kind: "maxBuilderFee",
+            user,
+            builder,
+        };
+        self.http.post_json(&url, &body).await
+    }
+
+    /// Get user's TWAP slice fills
+    pub async fn user_twap_slice_fills(&self, user: &str) -> Result<Vec<TwapSliceFill>, DexError> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            #[serde(rename = "type")]
+            kind: &'static str,
+            user: &'a str,
+        }
+
+        let url = format!("{}/info", self.base);
+        let body = Body {
+            kind: "userTwapSliceFills",
+            user,
+        };
+        self.http.post_json(&url, &body).await
+    }
+
+    /// Get user's TWAP order history
+    pub async fn twap_history(&self, user: &str) -> Result<Vec<TwapHistoryEntry>, DexError> {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            #[serde(rename = "type")]
+            kind: &'static str,
+            user: &'a str,
+        }
+
+        let url = format!("{}/info", self.base);
+        let body = Body {
+            kind: "twapHistory",
+            user,
+        };
+        self.http.post_json(&url, &body).await
+    }
+
     /// Get user's funding payment history
     pub async fn user_funding(
         &self,
@@ -730,6 +793,126 @@ impl HlRest {
     }
 }
 
+/* ----- clearinghouseState wire format ----- */
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawClearinghouseState {
+    asset_positions: Vec<RawAssetPosition>,
+    cross_margin_summary: RawMarginSummary,
+    cross_maintenance_margin_used: String,
+    withdrawable: String,
+    time: u64,
+}
+
+#[derive(Deserialize)]
+struct RawAssetPosition {
+    position: RawPosition,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawPosition {
+    coin: String,
+    szi: String,
+    leverage: Option<Leverage>,
+    entry_px: Option<String>,
+    position_value: String,
+    unrealized_pnl: String,
+    return_on_equity: Option<String>,
+    margin_used: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawMarginSummary {
+    account_value: String,
+    total_margin_used: String,
+    total_ntl_pos: String,
+    total_raw_usd: String,
+}
+
+impl RawClearinghouseState {
+    fn into_user_state(self) -> Result<UserState, DexError> {
+        let asset_positions = self
+            .asset_positions
+            .into_iter()
+            .map(|p| -> Result<AssetPosition, DexError> {
+                let p = p.position;
+                let entry_px = p
+                    .entry_px
+                    .map(|px| {
+                        px.parse::<f64>()
+                            .map(price)
+                            .map_err(|_| DexError::Parse("Invalid entry price".into()))
+                    })
+                    .transpose()?;
+                Ok(AssetPosition {
+                    coin: p.coin,
+                    hold: p.margin_used,
+                    szi: p.szi,
+                    leverage: p.leverage,
+                    entry_px,
+                    position_value: p.position_value,
+                    unrealized_pnl: p.unrealized_pnl,
+                    return_on_equity: p.return_on_equity,
+                })
+            })
+            .collect::<Result<Vec<_>, DexError>>()?;
+
+        let summary = self.cross_margin_summary;
+        Ok(UserState {
+            asset_positions,
+            cross_margin_summary: CrossMarginSummary {
+                account_value: summary.account_value,
+                total_margin_used: summary.total_margin_used,
+                total_ntl_pos: summary.total_ntl_pos,
+                total_raw_usd: summary.total_raw_usd,
+            },
+            cross_maintenance_margin_used: self.cross_maintenance_margin_used,
+            withdrawable: self.withdrawable,
+            time: self.time,
+        })
+    }
+}
+
+/// Unwrap an exchange action result: `{"status":"ok","response":{...}}` or
+/// `{"status":"err","response":"<message>"}`
+pub(crate) fn exchange_response(
+    mut resp: serde_json::Value,
+) -> Result<serde_json::Value, DexError> {
+    if resp["status"] == "ok" {
+        Ok(resp["response"].take())
+    } else {
+        let msg = match resp["response"].as_str() {
+            Some(msg) => msg.to_string(),
+            None => resp.to_string(),
+        };
+        Err(DexError::Exchange { code: None, msg })
+    }
+}
+
+/// `nSigFigs` must be 2-5, and `mantissa` (1, 2 or 5) is only allowed with 5
+pub(crate) fn check_book_aggregation(aggregation: &BookAggregation) -> Result<(), DexError> {
+    if !(2..=5).contains(&aggregation.n_sig_figs) {
+        return Err(DexError::Validation(format!(
+            "nSigFigs must be between 2 and 5, got {}",
+            aggregation.n_sig_figs
+        )));
+    }
+    match aggregation.mantissa {
+        Some(mantissa) if aggregation.n_sig_figs != 5 => Err(DexError::Validation(format!(
+            "mantissa {} requires nSigFigs 5",
+            mantissa
+        ))),
+        Some(mantissa) if ![1, 2, 5].contains(&mantissa) => Err(DexError::Validation(format!(
+            "mantissa must be 1, 2 or 5, got {}",
+            mantissa
+        ))),
+        _ => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -758,7 +941,7 @@ mod tests {
     #[test]
     fn test_trade_parsing() {
         // Test parsing of raw trade data
-        let raw_trades = vec![
+        let raw_trades = [
             json!({
                 "side": "B",
                 "px": "50000.5",
@@ -882,6 +1065,26 @@ mod tests {
         assert!(!asks.is_empty());
     }
 
+    #[test]
+    fn test_book_aggregation() {
+        let coarse = BookAggregation::sig_figs(5).with_mantissa(2);
+        assert_eq!(
+            serde_json::to_value(coarse).unwrap(),
+            json!({"nSigFigs": 5, "mantissa": 2})
+        );
+        assert_eq!(
+            serde_json::to_value(BookAggregation::sig_figs(3)).unwrap(),
+            json!({"nSigFigs": 3})
+        );
+
+        assert!(check_book_aggregation(&coarse).is_ok());
+        assert!(check_book_aggregation(&BookAggregation::sig_figs(2)).is_ok());
+        assert!(check_book_aggregation(&BookAggregation::sig_figs(6)).is_err());
+        assert!(check_book_aggregation(&BookAggregation::sig_figs(1)).is_err());
+        assert!(check_book_aggregation(&BookAggregation::sig_figs(4).with_mantissa(2)).is_err());
+        assert!(check_book_aggregation(&BookAggregation::sig_figs(5).with_mantissa(3)).is_err());
+    }
+
     #[test]
     fn test_new_endpoint_request_bodies() {
         // Test clearinghouseState request body
@@ -982,12 +1185,7 @@ mod tests {
                 "totalRawUsd": "10000.0"
             },
             "crossMaintenanceMarginUsed": "250.0",
-            "withdrawalsUsed": [
-                {
-                    "used": "0.0",
-                    "limit": "1000.0"
-                }
-            ],
+            "withdrawable": "9750.0",
             "time": 1234567890000u64
         });
 
@@ -999,6 +1197,132 @@ mod tests {
         assert!(!asset_positions.is_empty());
     }
 
+    #[test]
+    fn test_spot_balances_parsing() {
+        let raw = json!({
+            "balances": [
+                {"coin": "USDC", "token": 0, "hold": "0.0", "total": "14.625485", "entryNtl": "0.0"},
+                {"coin": "PURR", "token": 1, "hold": "2000.0", "total": "2000.0", "entryNtl": "1234.56"}
+            ]
+        });
+
+        let balances: SpotBalances = serde_json::from_value(raw).unwrap();
+        assert_eq!(balances.balances.len(), 2);
+        assert_eq!(balances.balances[0].coin, "USDC");
+        assert_eq!(balances.balances[0].total, "14.625485");
+        assert_eq!(balances.balances[1].token, 1);
+        assert_eq!(balances.balances[1].hold, "2000.0");
+        assert_eq!(balances.balances[1].entry_ntl, "1234.56");
+    }
+
+    #[test]
+    fn test_twap_responses_parsing() {
+        let raw = json!([{
+            "fill": {
+                "closedPnl": "0.0",
+                "coin": "AVAX",
+                "crossed": true,
+                "dir": "Open Long",
+                "hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "oid": 90542681,
+                "px": "18.435",
+                "side": "B",
+                "startPosition": "26.86",
+                "sz": "93.53",
+                "time": 1681222254710u64,
+                "fee": "0.0",
+                "feeToken": "USDC",
+                "tid": 118906512037719u64
+            },
+            "twapId": 3156
+        }]);
+        let fills: Vec<TwapSliceFill> = serde_json::from_value(raw).unwrap();
+        assert_eq!(fills[0].twap_id, 3156);
+        assert_eq!(fills[0].fill.coin, "AVAX");
+        assert_eq!(fills[0].fill.start_position, "26.86");
+        assert_eq!(fills[0].fill.closed_pnl, "0.0");
+
+        let raw = json!([{
+            "time": 1730000000,
+            "state": {
+                "coin": "BTC",
+                "user": "0x0000000000000000000000000000000000000001",
+                "side": "A",
+                "sz": "0.5",
+                "executedSz": "0.1",
+                "executedNtl": "6500.0",
+                "minutes": 30,
+                "reduceOnly": false,
+                "randomize": true,
+                "timestamp": 1730000000000u64
+            },
+            "status": {"status": "terminated"},
+            "twapId": 77
+        }]);
+        let history: Vec<TwapHistoryEntry> = serde_json::from_value(raw).unwrap();
+        assert_eq!(history[0].twap_id, Some(77));
+        assert_eq!(history[0].status.status, "terminated");
+        assert_eq!(history[0].state.executed_sz, "0.1");
+        assert_eq!(history[0].state.minutes, 30);
+        assert!(history[0].state.randomize);
+    }
+
+    #[test]
+    fn test_clearinghouse_state_parsing() {
+        let raw = json!({
+            "assetPositions": [{
+                "position": {
+                    "coin": "ETH",
+                    "cumFunding": {"allTime": "514.085417", "sinceChange": "0.0", "sinceOpen": "0.0"},
+                    "entryPx": "2986.3",
+                    "leverage": {"rawUsd": "-95.059824", "type": "isolated", "value": 20},
+                    "liquidationPx": "2866.26936529",
+                    "marginUsed": "4.967826",
+                    "maxLeverage": 50,
+                    "positionValue": "100.02765",
+                    "returnOnEquity": "-0.0026789",
+                    "szi": "-0.0335",
+                    "unrealizedPnl": "-0.0134"
+                },
+                "type": "oneWay"
+            }],
+            "crossMaintenanceMarginUsed": "0.0",
+            "crossMarginSummary": {
+                "accountValue": "13104.514502",
+                "totalMarginUsed": "0.0",
+                "totalNtlPos": "0.0",
+                "totalRawUsd": "13104.514502"
+            },
+            "marginSummary": {
+                "accountValue": "13109.482328",
+                "totalMarginUsed": "4.967826",
+                "totalNtlPos": "100.02765",
+                "totalRawUsd": "13009.454678"
+            },
+            "time": 1708622398623u64,
+            "withdrawable": "13104.514502"
+        });
+
+        let raw: RawClearinghouseState = serde_json::from_value(raw).unwrap();
+        let state = raw.into_user_state().unwrap();
+
+        assert_eq!(state.time, 1708622398623);
+        assert_eq!(state.cross_margin_summary.account_value, "13104.514502");
+        assert_eq!(state.withdrawable, "13104.514502");
+        assert_eq!(state.asset_positions.len(), 1);
+
+        let position = &state.asset_positions[0];
+        assert_eq!(position.coin, "ETH");
+        assert_eq!(position.szi, "-0.0335");
+        assert_eq!(position.entry_px, Some(price(2986.3)));
+        assert_eq!(position.hold, "4.967826");
+
+        let leverage = position.leverage.as_ref().unwrap();
+        assert_eq!(leverage.kind, LeverageType::Isolated);
+        assert_eq!(leverage.value, 20);
+        assert_eq!(leverage.raw_usd.as_deref(), Some("-95.059824"));
+    }
+
     #[test]
     fn test_funding_history_response_structure() {
         // Test expected FundingHistory response structure
@@ -1,6 +1,9 @@
 pub mod client;
 pub mod http;
+pub mod registry;
+pub mod rounding;
 pub mod signer;
 pub mod ws;
 
-pub use client::Hyperliquid;
+pub use client::{Hyperliquid, OrderTransport};
+pub use ws::ReconnectPolicy;
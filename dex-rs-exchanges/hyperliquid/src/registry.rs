@@ -0,0 +1,424 @@
+//! Cached coin ⇄ asset id lookups.
+//!
+//! Asset ids follow the exchange's numbering: perps use their universe index,
+//! spot pairs use `10000 + index`, and the n-th builder-deployed perp dex
+//! (1-based, in `perpDexs` order) uses `100000 + n * 10000 + index`.
+
+use std::{
+    collections::HashMap,
+    sync::RwLock,
+    time::{Duration, Instant},
+};
+
+use dex_rs_core::DexError;
+use dex_rs_types::{SpotMeta, UniverseMeta};
+
+use crate::http::HlRest;
+
+const SPOT_ASSET_OFFSET: u32 = 10_000;
+const PERP_DEX_ASSET_OFFSET: u32 = 100_000;
+const PERP_DEX_ASSET_STRIDE: u32 = 10_000;
+
+/// Minimum time between reloads triggered by lookups
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Trading metadata for a single perp or spot asset
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssetInfo {
+    /// Coin name as used on the wire (`BTC`, `@107`, `PURR/USDC`, `dex:COIN`)
+    pub name: String,
+    pub asset_id: u32,
+    pub sz_decimals: u32,
+    /// Zero for spot pairs
+    pub max_leverage: u32,
+    pub only_isolated: bool,
+    pub is_spot: bool,
+}
+
+#[derive(Default)]
+struct Assets {
+    by_name: HashMap<String, AssetInfo>,
+    by_id: HashMap<u32, String>,
+}
+
+impl Assets {
+    fn insert_perps(&mut self, meta: &UniverseMeta, offset: u32) {
+        for (index, asset) in meta.assets.iter().enumerate() {
+            self.insert(
+                &asset.name,
+                AssetInfo {
+                    name: asset.name.clone(),
+                    asset_id: offset + index as u32,
+                    sz_decimals: asset.sz_decimals,
+                    max_leverage: asset.max_leverage,
+                    only_isolated: asset.only_isolated,
+                    is_spot: false,
+                },
+            );
+        }
+    }
+
+    fn insert_spot(&mut self, meta: &SpotMeta) {
+        for pair in &meta.universe {
+            let Some(base) = pair
+                .tokens
+                .first()
+                .and_then(|&i| meta.tokens.iter().find(|token| token.index == i))
+            else {
+                continue;
+            };
+            let info = AssetInfo {
+                name: pair.name.clone(),
+                asset_id: SPOT_ASSET_OFFSET + pair.index,
+                sz_decimals: base.sz_decimals,
+                max_leverage: 0,
+                only_isolated: false,
+                is_spot: true,
+            };
+
+            // Non-canonical pairs are named `@{index}`; also accept `BASE/QUOTE`
+            let quote = pair
+                .tokens
+                .get(1)
+                .and_then(|&i| meta.tokens.iter().find(|token| token.index == i));
+            if let Some(quote) = quote {
+                let alias = format!("{}/{}", base.name, quote.name);
+                self.by_name
+                    .entry(alias.to_ascii_lowercase())
+                    .or_insert_with(|| info.clone());
+            }
+            self.insert(&pair.name, info);
+        }
+    }
+
+    fn insert(&mut self, name: &str, info: AssetInfo) {
+        self.by_id.insert(info.asset_id, info.name.clone());
+        self.by_name.insert(name.to_ascii_lowercase(), info);
+    }
+}
+
+/// Coin ⇄ asset id ⇄ szDecimals lookups loaded from perp, spot and perp dex meta
+pub struct AssetRegistry {
+    assets: RwLock<Assets>,
+    loaded_at: RwLock<Instant>,
+    refresh_interval: Option<Duration>,
+    /// Last load or lookup-triggered reload attempt
+    refresh_attempted_at: RwLock<Instant>,
+}
+
+impl AssetRegistry {
+    /// Load all asset metadata from the info endpoint
+    pub async fn load(rest: &HlRest, refresh_interval: Option<Duration>) -> Result<Self, DexError> {
+        Ok(Self {
+            assets: RwLock::new(fetch_assets(rest).await?),
+            loaded_at: RwLock::new(Instant::now()),
+            refresh_interval,
+            refresh_attempted_at: RwLock::new(Instant::now()),
+        })
+    }
+
+    /// Build a registry from already-fetched metadata
+    pub fn from_meta(
+        perp: &UniverseMeta,
+        spot: Option<&SpotMeta>,
+        perp_dexs: &[UniverseMeta],
+    ) -> Self {
+        Self {
+            assets: RwLock::new(build_assets(perp, spot, perp_dexs)),
+            loaded_at: RwLock::new(Instant::now()),
+            refresh_interval: None,
+            refresh_attempted_at: RwLock::new(Instant::now()),
+        }
+    }
+
+    /// Look up an asset by coin name (case-insensitive)
+    pub fn get(&self, coin: &str) -> Option<AssetInfo> {
+        self.assets
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .by_name
+            .get(&coin.to_ascii_lowercase())
+            .cloned()
+    }
+
+    /// Look up a coin name by asset id
+    pub fn coin(&self, asset_id: u32) -> Option<String> {
+        self.assets
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .by_id
+            .get(&asset_id)
+            .cloned()
+    }
+
+    /// Resolve a coin, reloading first if the cache is stale or misses. One caller
+    /// reloads at most every 10 seconds while the rest are served from the cache.
+    pub async fn resolve(&self, rest: &HlRest, coin: &str) -> Result<AssetInfo, DexError> {
+        let cached = self.get(coin);
+        if (cached.is_none() || self.is_stale()) && self.claim_refresh() {
+            // A failed reload leaves the cached entry in service until the next attempt
+            if let Err(e) = self.refresh(rest).await {
+                if cached.is_none() {
+                    return Err(e);
+                }
+            }
+        }
+        self.get(coin)
+            .or(cached)
+            .ok_or_else(|| DexError::Other(format!("Asset not found: {}", coin)))
+    }
+
+    /// Reload all metadata from the info endpoint
+    pub async fn refresh(&self, rest: &HlRest) -> Result<(), DexError> {
+        let assets = fetch_assets(rest).await?;
+        *self.assets.write().unwrap_or_else(|e| e.into_inner()) = assets;
+        *self.loaded_at.write().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+        Ok(())
+    }
+
+    /// Whether a lookup may trigger a reload now; claims the slot if so
+    fn claim_refresh(&self) -> bool {
+        let mut attempted_at = self
+            .refresh_attempted_at
+            .write()
+            .unwrap_or_else(|e| e.into_inner());
+        if attempted_at.elapsed() < MIN_REFRESH_INTERVAL {
+            return false;
+        }
+        *attempted_at = Instant::now();
+        true
+    }
+
+    fn is_stale(&self) -> bool {
+        self.refresh_interval.is_some_and(|interval| {
+            self.loaded_at
+                .read()
+                .unwrap_or_else(|e| e.into_inner())
+                .elapsed()
+                >= interval
+        })
+    }
+}
+
+async fn fetch_assets(rest: &HlRest) -> Result<Assets, DexError> {
+    let perp = rest.meta(None).await?;
+    let spot = rest.spot_meta().await?;
+
+    // First entry is the default dex (null); the rest are builder-deployed
+    let dex_names: Vec<String> = rest
+        .perp_dexs()
+        .await?
+        .as_array()
+        .map(|dexs| {
+            dexs.iter()
+                .skip(1)
+                .filter_map(|dex| dex["name"].as_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut perp_dexs = Vec::with_capacity(dex_names.len());
+    for name in &dex_names {
+        perp_dexs.push(rest.meta(Some(name)).await?);
+    }
+
+    Ok(build_assets(&perp, Some(&spot), &perp_dexs))
+}
+
+fn build_assets(
+    perp: &UniverseMeta,
+    spot: Option<&SpotMeta>,
+    perp_dexs: &[UniverseMeta],
+) -> Assets {
+    let mut assets = Assets::default();
+    assets.insert_perps(perp, 0);
+    for (i, meta) in perp_dexs.iter().enumerate() {
+        let offset = PERP_DEX_ASSET_OFFSET + (i as u32 + 1) * PERP_DEX_ASSET_STRIDE;
+        assets.insert_perps(meta, offset);
+    }
+    if let Some(spot) = spot {
+        assets.insert_spot(spot);
+    }
+    assets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use bytes::Bytes;
+    use dex_rs_core::http::{Http, HttpTransport};
+    use dex_rs_types::{AssetMeta, SpotAssetMeta, SpotUniverseItem};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Counts info requests and fails them all
+    struct CountingTransport {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl HttpTransport for CountingTransport {
+        async fn call(
+            &self,
+            _req: http::Request<Vec<u8>>,
+        ) -> Result<http::Response<Bytes>, DexError> {
+            self.calls.fetch_add(1, Ordering::Relaxed);
+            Err(DexError::Other("offline".into()))
+        }
+    }
+
+    fn perp_meta(names: &[(&str, u32)]) -> UniverseMeta {
+        UniverseMeta {
+            assets: names
+                .iter()
+                .map(|&(name, sz_decimals)| AssetMeta {
+                    name: name.into(),
+                    sz_decimals,
+                    max_leverage: 50,
+                    only_isolated: false,
+                })
+                .collect(),
+            universe: vec![],
+        }
+    }
+
+    fn token(name: &str, index: u32, sz_decimals: u32) -> SpotAssetMeta {
+        SpotAssetMeta {
+            name: name.into(),
+            sz_decimals,
+            wei_decimals: 8,
+            index,
+            token_id: format!("0x{:032x}", index),
+            is_canonical: true,
+        }
+    }
+
+    fn spot_meta() -> SpotMeta {
+        SpotMeta {
+            tokens: vec![
+                token("USDC", 0, 8),
+                token("PURR", 1, 0),
+                token("HYPE", 150, 2),
+            ],
+            universe: vec![
+                SpotUniverseItem {
+                    tokens: vec![1, 0],
+                    name: "PURR/USDC".into(),
+                    index: 0,
+                    is_canonical: true,
+                },
+                SpotUniverseItem {
+                    tokens: vec![150, 0],
+                    name: "@107".into(),
+                    index: 107,
+                    is_canonical: false,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_perp_lookup() {
+        let registry = AssetRegistry::from_meta(&perp_meta(&[("BTC", 5), ("ETH", 4)]), None, &[]);
+
+        let eth = registry.get("eth").unwrap();
+        assert_eq!(eth.name, "ETH");
+        assert_eq!(eth.asset_id, 1);
+        assert_eq!(eth.sz_decimals, 4);
+        assert!(!eth.is_spot);
+
+        assert_eq!(registry.coin(0).as_deref(), Some("BTC"));
+        assert!(registry.get("DOGE").is_none());
+    }
+
+    #[test]
+    fn test_spot_lookup() {
+        let registry = AssetRegistry::from_meta(&perp_meta(&[("BTC", 5)]), Some(&spot_meta()), &[]);
+
+        let purr = registry.get("PURR/USDC").unwrap();
+        assert_eq!(purr.asset_id, 10_000);
+        assert_eq!(purr.sz_decimals, 0);
+        assert!(purr.is_spot);
+
+        let hype = registry.get("@107").unwrap();
+        assert_eq!(hype.asset_id, 10_107);
+        assert_eq!(hype.sz_decimals, 2);
+
+        // Token-pair alias resolves to the wire name
+        assert_eq!(registry.get("HYPE/USDC").unwrap().name, "@107");
+        assert_eq!(registry.coin(10_107).as_deref(), Some("@107"));
+    }
+
+    #[test]
+    fn test_perp_dex_lookup() {
+        let registry = AssetRegistry::from_meta(
+            &perp_meta(&[("BTC", 5)]),
+            None,
+            &[
+                perp_meta(&[("test:ABC", 1)]),
+                perp_meta(&[("xyz:AAA", 2), ("xyz:BBB", 3)]),
+            ],
+        );
+
+        assert_eq!(registry.get("test:ABC").unwrap().asset_id, 110_000);
+        assert_eq!(registry.get("xyz:BBB").unwrap().asset_id, 120_001);
+        assert_eq!(registry.coin(120_000).as_deref(), Some("xyz:AAA"));
+    }
+
+    #[tokio::test]
+    async fn test_miss_refresh_is_throttled() {
+        let transport = Arc::new(CountingTransport {
+            calls: AtomicUsize::new(0),
+        });
+        let rest = HlRest::new(Http::new(transport.clone()), false);
+        let registry = AssetRegistry::from_meta(&perp_meta(&[("BTC", 5)]), None, &[]);
+
+        // Just loaded: misses don't reload
+        for _ in 0..3 {
+            let err = registry.resolve(&rest, "BTCC").await.unwrap_err();
+            assert!(err.to_string().contains("Asset not found"));
+        }
+        assert_eq!(transport.calls.load(Ordering::Relaxed), 0);
+
+        // Once the interval has passed, one miss reloads and the next is throttled again
+        *registry.refresh_attempted_at.write().unwrap() = Instant::now() - MIN_REFRESH_INTERVAL;
+        assert!(registry.resolve(&rest, "BTCC").await.is_err());
+        assert_eq!(transport.calls.load(Ordering::Relaxed), 1);
+        let err = registry.resolve(&rest, "BTCC").await.unwrap_err();
+        assert!(err.to_string().contains("Asset not found"));
+        assert_eq!(transport.calls.load(Ordering::Relaxed), 1);
+
+        // Hits never reload
+        assert_eq!(registry.resolve(&rest, "btc").await.unwrap().asset_id, 0);
+        assert_eq!(transport.calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn test_stale_refresh_falls_back_to_cache() {
+        let transport = Arc::new(CountingTransport {
+            calls: AtomicUsize::new(0),
+        });
+        let rest = HlRest::new(Http::new(transport.clone()), false);
+        let mut registry = AssetRegistry::from_meta(&perp_meta(&[("BTC", 5)]), None, &[]);
+        registry.refresh_interval = Some(Duration::ZERO);
+        *registry.refresh_attempted_at.write().unwrap() = Instant::now() - MIN_REFRESH_INTERVAL;
+
+        // One lookup tries to reload; its failure and the concurrent lookups are
+        // served from the cache
+        let (a, b, c) = tokio::join!(
+            registry.resolve(&rest, "BTC"),
+            registry.resolve(&rest, "BTC"),
+            registry.resolve(&rest, "btc"),
+        );
+        for info in [a, b, c] {
+            assert_eq!(info.unwrap().asset_id, 0);
+        }
+        assert_eq!(transport.calls.load(Ordering::Relaxed), 1);
+
+        // Still stale, but the next reload waits for the interval
+        assert_eq!(registry.resolve(&rest, "BTC").await.unwrap().name, "BTC");
+        assert_eq!(transport.calls.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,214 @@
+//! Price and size rounding driven by asset metadata.
+//!
+//! Hyperliquid accepts prices with at most 5 significant figures and at most
+//! `6 - szDecimals` decimals for perps (`8 - szDecimals` for spot); integer
+//! prices are always allowed. Sizes are rounded to `szDecimals` decimals.
+
+use dex_rs_core::DexError;
+use dex_rs_types::{price, qty, OrderReq};
+
+const MAX_PERP_DECIMALS: u32 = 6;
+const MAX_SPOT_DECIMALS: u32 = 8;
+const SIG_FIGS: usize = 5;
+
+/// Round a price to the exchange's tick rules
+pub fn round_price(px: f64, sz_decimals: u32, is_spot: bool) -> f64 {
+    // Integer prices are valid regardless of significant figures
+    if px.fract() == 0.0 {
+        return px;
+    }
+    let max_decimals = if is_spot {
+        MAX_SPOT_DECIMALS
+    } else {
+        MAX_PERP_DECIMALS
+    };
+    let decimals = max_decimals.saturating_sub(sz_decimals) as usize;
+    let px: f64 = format!("{:.*e}", SIG_FIGS - 1, px).parse().unwrap_or(px);
+    round_decimals(px, decimals)
+}
+
+/// Round a size to the asset's lot precision
+pub fn round_size(sz: f64, sz_decimals: u32) -> f64 {
+    round_decimals(sz, sz_decimals as usize)
+}
+
+fn round_decimals(x: f64, decimals: usize) -> f64 {
+    format!("{:.*}", decimals, x).parse().unwrap_or(x)
+}
+
+/// Format a number for the wire: at most 8 decimals with trailing zeros removed
+pub fn float_to_wire(x: f64) -> String {
+    let s = format!("{:.8}", x);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// Round an order's price, trigger price and size in place.
+///
+/// With `strict` set, any value that would change is reported as a
+/// [`DexError::Validation`] instead of being rounded silently.
+pub fn normalize_order(
+    req: &mut OrderReq,
+    sz_decimals: u32,
+    is_spot: bool,
+    strict: bool,
+) -> Result<(), DexError> {
+    let px = round_price(*req.px, sz_decimals, is_spot);
+    let trigger_px = req
+        .trigger
+        .map(|trigger| round_price(*trigger.trigger_px, sz_decimals, is_spot));
+
+    if strict {
+        check_unchanged("price", *req.px, px)?;
+        if let (Some(trigger), Some(rounded)) = (req.trigger, trigger_px) {
+            check_unchanged("trigger price", *trigger.trigger_px, rounded)?;
+        }
+    }
+    let sz = normalize_size(&req.coin, *req.qty, sz_decimals, strict)?;
+
+    req.px = price(px);
+    req.qty = qty(sz);
+    if let (Some(trigger), Some(rounded)) = (req.trigger.as_mut(), trigger_px) {
+        trigger.trigger_px = price(rounded);
+    }
+    Ok(())
+}
+
+/// Round a size to the asset's lot precision, rejecting sizes that round to zero
+/// (or any rounding at all when `strict`)
+pub fn normalize_size(
+    coin: &str,
+    sz: f64,
+    sz_decimals: u32,
+    strict: bool,
+) -> Result<f64, DexError> {
+    let rounded = round_size(sz, sz_decimals);
+    if strict {
+        check_unchanged("size", sz, rounded)?;
+    }
+    if rounded == 0.0 {
+        return Err(DexError::Validation(format!(
+            "{} size {} rounds to zero at {} decimals",
+            coin, sz, sz_decimals
+        )));
+    }
+    Ok(rounded)
+}
+
+fn check_unchanged(what: &str, original: f64, rounded: f64) -> Result<(), DexError> {
+    if (original - rounded).abs() > 1e-12 {
+        return Err(DexError::Validation(format!(
+            "{} {} is not a valid increment (nearest: {})",
+            what, original, rounded
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dex_rs_types::{Tif, Tpsl, Trigger};
+
+    fn order(px: f64, sz: f64) -> OrderReq {
+        OrderReq {
+            coin: "ETH".into(),
+            is_buy: true,
+            px: price(px),
+            qty: qty(sz),
+            tif: Tif::Gtc,
+            reduce_only: false,
+            cloid: None,
+            trigger: None,
+        }
+    }
+
+    #[test]
+    fn test_round_price() {
+        // Perps: 5 significant figures, at most 6 - szDecimals decimals
+        assert_eq!(round_price(1234.5678, 4, false), 1234.6);
+        assert_eq!(round_price(0.123456789, 0, false), 0.12346);
+        assert_eq!(round_price(0.0123456, 3, false), 0.012);
+        assert_eq!(round_price(0.1 + 0.2, 1, false), 0.3);
+
+        // Integer prices pass through untouched
+        assert_eq!(round_price(123456.0, 5, false), 123456.0);
+
+        // Spot allows 8 - szDecimals decimals
+        assert_eq!(round_price(0.0123456, 3, true), 0.01235);
+    }
+
+    #[test]
+    fn test_round_size() {
+        assert_eq!(round_size(1.23456, 2), 1.23);
+        assert_eq!(round_size(0.1 + 0.2, 4), 0.3);
+        assert_eq!(round_size(12.7, 0), 13.0);
+    }
+
+    #[test]
+    fn test_float_to_wire() {
+        assert_eq!(float_to_wire(0.1 + 0.2), "0.3");
+        assert_eq!(float_to_wire(100.0), "100");
+        assert_eq!(float_to_wire(1670.1), "1670.1");
+        assert_eq!(float_to_wire(0.00001), "0.00001");
+        assert_eq!(float_to_wire(-0.0), "0");
+    }
+
+    #[test]
+    fn test_normalize_order() {
+        let mut req = order(1234.5678, 0.123456);
+        req.trigger = Some(Trigger::stop_market(price(1200.04321)));
+        normalize_order(&mut req, 4, false, false).unwrap();
+        assert_eq!(*req.px, 1234.6);
+        assert_eq!(*req.qty, 0.1235);
+        assert_eq!(*req.trigger.unwrap().trigger_px, 1200.0);
+        assert_eq!(req.trigger.unwrap().tpsl, Tpsl::Sl);
+    }
+
+    #[test]
+    fn test_normalize_order_strict() {
+        let mut req = order(1234.6, 0.1235);
+        normalize_order(&mut req, 4, false, true).unwrap();
+        assert_eq!(*req.px, 1234.6);
+
+        let mut req = order(1234.5678, 0.1235);
+        assert!(matches!(
+            normalize_order(&mut req, 4, false, true),
+            Err(DexError::Validation(_))
+        ));
+        // The request is left untouched on failure
+        assert_eq!(*req.px, 1234.5678);
+
+        let mut req = order(1234.6, 0.123456);
+        assert!(matches!(
+            normalize_order(&mut req, 4, false, true),
+            Err(DexError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_size() {
+        assert_eq!(normalize_size("BTC", 0.123456, 4, false).unwrap(), 0.1235);
+        assert!(matches!(
+            normalize_size("BTC", 0.123456, 4, true),
+            Err(DexError::Validation(_))
+        ));
+        assert!(matches!(
+            normalize_size("BTC", 0.00001, 2, false),
+            Err(DexError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_normalize_order_zero_size() {
+        let mut req = order(1234.6, 0.00001);
+        assert!(matches!(
+            normalize_order(&mut req, 2, false, false),
+            Err(DexError::Validation(_))
+        ));
+    }
+}
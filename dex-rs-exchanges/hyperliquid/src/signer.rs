@@ -1,15 +1,52 @@
 //! Hyperliquid signing implementation.
 //!
-//! Based on the official Hyperliquid protocol, this implements proper MessagePack
-//! encoding and EIP-712 signing for order placement.
+//! Based on the official Hyperliquid protocol. L1 actions (orders, cancels, ...) are
+//! MessagePack encoded and hashed together with the nonce and optional vault address.
+//! That hash becomes the `connectionId` of a "phantom agent" which is signed as an
+//! EIP-712 typed message.
+//!
+//! User-signed actions (transfers, withdrawals, approvals) are instead signed
+//! directly as `HyperliquidTransaction:*` EIP-712 structs under the
+//! `HyperliquidSignTransaction` domain.
 
 use alloy::{
-    primitives::{keccak256, Address},
+    primitives::{keccak256, Address, B256, U256},
     signers::{local::PrivateKeySigner, Signer},
+    sol_types::{eip712_domain, Eip712Domain, SolStruct},
 };
 use dex_rs_core::DexError;
-use dex_rs_types::OrderReq;
-use serde::Serialize;
+use dex_rs_types::{Grouping, OrderReq, Tpsl};
+use serde::{Deserialize, Serialize};
+
+use crate::rounding::float_to_wire;
+
+alloy::sol! {
+    struct Agent {
+        string source;
+        bytes32 connectionId;
+    }
+}
+
+/// EIP-712 domain used for every L1 action
+const L1_DOMAIN: Eip712Domain = eip712_domain! {
+    name: "Exchange",
+    version: "1",
+    chain_id: 1337,
+    verifying_contract: Address::ZERO,
+};
+
+/// Chain id sent as `signatureChainId` with user-signed actions (Arbitrum Sepolia)
+pub const SIGNATURE_CHAIN_ID: u64 = 0x66eee;
+
+/// EIP-712 domain used for user-signed actions
+fn user_signed_domain(chain_id: u64) -> Eip712Domain {
+    eip712_domain! {
+        name: "HyperliquidSignTransaction",
+        version: "1",
+        chain_id: chain_id,
+        verifying_contract: Address::ZERO,
+    }
+}
 
 #[derive(Clone)]
 pub struct HlSigner {
@@ -17,6 +54,24 @@ pub struct HlSigner {
     address: Address,
 }
 
+/// Signature object in the `{r, s, v}` form expected by the `/exchange` endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HlSignature {
+    pub r: String,
+    pub s: String,
+    pub v: u8,
+}
+
+impl From<alloy::signers::Signature> for HlSignature {
+    fn from(sig: alloy::signers::Signature) -> Self {
+        Self {
+            r: format!("{:#x}", sig.r()),
+            s: format!("{:#x}", sig.s()),
+            v: 27 + sig.v() as u8,
+        }
+    }
+}
+
 impl HlSigner {
     pub fn from_hex_key(pk_hex: &str) -> Result<Self, DexError> {
         let wallet = pk_hex
@@ -31,81 +86,212 @@ impl HlSigner {
         format!("{:x}", self.address)
     }
 
-    /// Sign a user action (like placing an order) using MessagePack encoding
-    pub async fn sign_order(
+    /// Sign an L1 action (order, cancel, ...) for submission to `/exchange`
+    pub async fn sign_l1_action<A: Serialize>(
         &self,
-        ord: &OrderReq,
+        action: &A,
         nonce: u64,
-        asset_index: u32,
-        cloid: &str,
-    ) -> Result<String, DexError> {
-        let action = OrderAction::from_req(ord, asset_index, cloid);
-        let user_signed_action = UserSignedAction { action, nonce };
-
-        // Hyperliquid requires MessagePack encoding before signing
-        let msgpack_bytes = rmp_serde::to_vec(&user_signed_action)
-            .map_err(|e| DexError::Other(format!("MessagePack encoding failed: {}", e)))?;
-
-        // Hash the MessagePack bytes
-        let hash = keccak256(&msgpack_bytes);
+        vault_address: Option<&str>,
+        is_mainnet: bool,
+    ) -> Result<HlSignature, DexError> {
+        let agent = Agent {
+            source: if is_mainnet { "a" } else { "b" }.to_string(),
+            connectionId: action_hash(action, nonce, vault_address)?,
+        };
+        self.sign_digest(agent.eip712_signing_hash(&L1_DOMAIN))
+            .await
+    }
+
+    /// Sign a user-signed action (transfer, withdrawal, ...) for submission to `/exchange`
+    pub async fn sign_user_signed_action<A: UserSignedAction>(
+        &self,
+        action: &A,
+    ) -> Result<HlSignature, DexError> {
+        self.sign_digest(user_signed_signing_hash(action)).await
+    }
+
+    async fn sign_digest(&self, hash: B256) -> Result<HlSignature, DexError> {
         let sig = self
             .wallet
             .sign_hash(&hash)
             .await
             .map_err(|e| DexError::Other(e.to_string()))?;
-
-        // Format as hex string
-        Ok(format!("0x{}", hex::encode(sig.as_bytes())))
+        Ok(sig.into())
     }
 }
 
-/// User-signed action wrapper for MessagePack encoding
-#[derive(Debug, Serialize)]
-struct UserSignedAction {
-    action: OrderAction,
+/// Compute the L1 action hash: `keccak256(msgpack(action) ‖ nonce ‖ vault)`
+pub fn action_hash<A: Serialize>(
+    action: &A,
     nonce: u64,
+    vault_address: Option<&str>,
+) -> Result<B256, DexError> {
+    // Hyperliquid hashes actions as msgpack maps, so field names must be kept
+    let mut data = rmp_serde::to_vec_named(action)
+        .map_err(|e| DexError::Other(format!("MessagePack encoding failed: {}", e)))?;
+    data.extend_from_slice(&nonce.to_be_bytes());
+    match vault_address {
+        Some(vault) => {
+            data.push(1);
+            data.extend_from_slice(parse_address(vault)?.as_slice());
+        }
+        None => data.push(0),
+    }
+    Ok(keccak256(&data))
+}
+
+/// A single field value of a user-signed EIP-712 struct
+pub enum TypedValue<'a> {
+    String(&'a str),
+    Uint64(u64),
+    Bool(bool),
+    Address(Address),
+}
+
+impl TypedValue<'_> {
+    /// EIP-712 `encodeData` word for this value
+    fn encode(&self) -> B256 {
+        match self {
+            TypedValue::String(s) => keccak256(s.as_bytes()),
+            TypedValue::Uint64(n) => B256::from(U256::from(*n)),
+            TypedValue::Bool(b) => B256::from(U256::from(*b as u8)),
+            TypedValue::Address(a) => a.into_word(),
+        }
+    }
+}
+
+/// An action signed directly by the user's wallet rather than through the phantom agent
+pub trait UserSignedAction: Serialize + Sync {
+    /// EIP-712 type string, e.g. `HyperliquidTransaction:UsdSend(string hyperliquidChain,...)`
+    const EIP712_TYPE: &'static str;
+
+    /// Field values in the order they appear in [`Self::EIP712_TYPE`]
+    fn typed_values(&self) -> Vec<TypedValue<'_>>;
+
+    /// Nonce posted alongside the action (its `time` or `nonce` field)
+    fn nonce(&self) -> u64;
+}
+
+/// EIP-712 struct hash of a user-signed action
+pub fn user_signed_struct_hash<A: UserSignedAction>(action: &A) -> B256 {
+    let mut data = Vec::with_capacity(32 * 8);
+    data.extend_from_slice(keccak256(A::EIP712_TYPE.as_bytes()).as_slice());
+    for value in action.typed_values() {
+        data.extend_from_slice(value.encode().as_slice());
+    }
+    keccak256(&data)
+}
+
+/// EIP-712 digest (`0x1901 ‖ domainSeparator ‖ structHash`) of a user-signed action
+pub fn user_signed_signing_hash<A: UserSignedAction>(action: &A) -> B256 {
+    let mut data = Vec::with_capacity(66);
+    data.extend_from_slice(&[0x19, 0x01]);
+    data.extend_from_slice(
+        user_signed_domain(SIGNATURE_CHAIN_ID)
+            .separator()
+            .as_slice(),
+    );
+    data.extend_from_slice(user_signed_struct_hash(action).as_slice());
+    keccak256(&data)
+}
+
+fn parse_address(addr: &str) -> Result<Address, DexError> {
+    addr.parse::<Address>()
+        .map_err(|e| DexError::Other(format!("Invalid address {}: {}", addr, e)))
+}
+
+/// Convert a client order ID into Hyperliquid's 128-bit hex wire format.
+///
+/// Accepts either a `0x`-prefixed 32-digit hex string or an ID produced by
+/// [`dex_rs_types::generate_cloid`], whose timestamp and counter are packed into
+/// the upper and lower 64 bits.
+pub fn cloid_to_wire(cloid: &str) -> Result<String, DexError> {
+    if let Some(hex_part) = cloid.strip_prefix("0x") {
+        if hex_part.len() == 32 && hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Ok(cloid.to_lowercase());
+        }
+    } else if let Some((ts, counter)) = cloid.split_once('_') {
+        if let (Ok(ts), Ok(counter)) = (ts.parse::<u64>(), counter.parse::<u64>()) {
+            return Ok(format!("0x{:016x}{:016x}", ts, counter));
+        }
+    }
+    Err(DexError::Other(format!(
+        "Invalid cloid {}: expected 0x-prefixed 128-bit hex",
+        cloid
+    )))
 }
 
 /// Order action payload - field order is critical for MessagePack
 #[derive(Debug, Serialize)]
-struct OrderAction {
+pub(crate) struct OrderAction {
     #[serde(rename = "type")]
     action_type: String,
     orders: Vec<Order>,
     grouping: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    builder: Option<BuilderWire>,
+}
+
+/// Builder code attached to an order action; `f` is the fee in tenths of a basis point
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct BuilderWire {
+    b: String,
+    f: u32,
+}
+
+impl BuilderWire {
+    pub(crate) fn new(builder: &str, fee: u32) -> Self {
+        BuilderWire {
+            b: builder.to_lowercase(),
+            f: fee,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
-struct Order {
+pub(crate) struct Order {
     a: u32,       // asset index
     b: bool,      // is_buy
     p: String,    // price
     s: String,    // size
     r: bool,      // reduce_only
     t: OrderType, // order type
-    c: String,    // client_order_id
+    #[serde(skip_serializing_if = "Option::is_none")]
+    c: Option<String>, // client_order_id
 }
 
 #[derive(Debug, Serialize)]
-struct OrderType {
-    limit: LimitOrder,
+#[serde(rename_all = "camelCase")]
+enum OrderType {
+    Limit {
+        tif: String, // time in force
+    },
+    #[serde(rename_all = "camelCase")]
+    Trigger {
+        is_market: bool,
+        trigger_px: String,
+        tpsl: String, // "tp" | "sl"
+    },
 }
 
-#[derive(Debug, Serialize)]
-struct LimitOrder {
-    tif: String, // time in force
-}
-
-impl OrderAction {
-    fn from_req(req: &OrderReq, asset_index: u32, cloid: &str) -> Self {
-        let order = Order {
+impl Order {
+    pub(crate) fn from_req(req: &OrderReq, asset_index: u32, cloid: Option<&str>) -> Self {
+        Order {
             a: asset_index,
             b: req.is_buy,
-            p: format!("{}", *req.px),
-            s: format!("{}", *req.qty),
+            p: float_to_wire(*req.px),
+            s: float_to_wire(*req.qty),
             r: req.reduce_only,
-            t: OrderType {
-                limit: LimitOrder {
+            t: match req.trigger {
+                Some(trigger) => OrderType::Trigger {
+                    is_market: trigger.is_market,
+                    trigger_px: float_to_wire(*trigger.trigger_px),
+                    tpsl: match trigger.tpsl {
+                        Tpsl::Tp => "tp".to_string(),
+                        Tpsl::Sl => "sl".to_string(),
+                    },
+                },
+                None => OrderType::Limit {
                     tif: match req.tif {
                         dex_rs_types::Tif::Ioc => "Ioc".to_string(),
                         dex_rs_types::Tif::Gtc => "Gtc".to_string(),
@@ -113,25 +299,772 @@ impl OrderAction {
                     },
                 },
             },
-            c: cloid.to_string(),
-        };
+            c: cloid.map(str::to_string),
+        }
+    }
+}
 
+impl OrderAction {
+    pub(crate) fn new(orders: Vec<Order>, grouping: Grouping) -> Self {
         OrderAction {
             action_type: "order".to_string(),
-            orders: vec![order],
-            grouping: "na".to_string(),
+            orders,
+            grouping: match grouping {
+                Grouping::Na => "na".to_string(),
+                Grouping::NormalTpsl => "normalTpsl".to_string(),
+                Grouping::PositionTpsl => "positionTpsl".to_string(),
+            },
+            builder: None,
+        }
+    }
+
+    /// Attach a builder code so the builder earns its fee on these orders
+    pub(crate) fn with_builder(mut self, builder: Option<BuilderWire>) -> Self {
+        self.builder = builder;
+        self
+    }
+}
+
+/// Batch modify action payload
+#[derive(Debug, Serialize)]
+pub(crate) struct BatchModifyAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    modifies: Vec<Modify>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Modify {
+    oid: OidOrCloid,
+    order: Order,
+}
+
+/// Orders are referenced by integer oid or by hex cloid string
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub(crate) enum OidOrCloid {
+    Oid(u64),
+    Cloid(String),
+}
+
+impl Modify {
+    pub(crate) fn new(oid: OidOrCloid, order: Order) -> Self {
+        Modify { oid, order }
+    }
+}
+
+impl BatchModifyAction {
+    pub(crate) fn new(modifies: Vec<Modify>) -> Self {
+        BatchModifyAction {
+            action_type: "batchModify".to_string(),
+            modifies,
+        }
+    }
+}
+
+/// Cancel-by-oid action payload
+#[derive(Debug, Serialize)]
+pub(crate) struct CancelAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    cancels: Vec<Cancel>,
+}
+
+#[derive(Debug, Serialize)]
+struct Cancel {
+    a: u32, // asset index
+    o: u64, // order id
+}
+
+impl CancelAction {
+    pub(crate) fn new(cancels: impl IntoIterator<Item = (u32, u64)>) -> Self {
+        CancelAction {
+            action_type: "cancel".to_string(),
+            cancels: cancels.into_iter().map(|(a, o)| Cancel { a, o }).collect(),
+        }
+    }
+}
+
+/// Cancel-by-cloid action payload
+#[derive(Debug, Serialize)]
+pub(crate) struct CancelByCloidAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    cancels: Vec<CancelByCloid>,
+}
+
+#[derive(Debug, Serialize)]
+struct CancelByCloid {
+    asset: u32,
+    cloid: String,
+}
+
+impl CancelByCloidAction {
+    pub(crate) fn new(cancels: impl IntoIterator<Item = (u32, String)>) -> Self {
+        CancelByCloidAction {
+            action_type: "cancelByCloid".to_string(),
+            cancels: cancels
+                .into_iter()
+                .map(|(asset, cloid)| CancelByCloid { asset, cloid })
+                .collect(),
+        }
+    }
+}
+
+/// Dead-man switch action: cancel all open orders at `time` (ms), or clear the schedule
+#[derive(Debug, Serialize)]
+pub(crate) struct ScheduleCancelAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time: Option<u64>,
+}
+
+impl ScheduleCancelAction {
+    pub(crate) fn new(time: Option<u64>) -> Self {
+        ScheduleCancelAction {
+            action_type: "scheduleCancel".to_string(),
+            time,
+        }
+    }
+}
+
+/// Leverage update action payload
+#[derive(Debug, Serialize)]
+pub(crate) struct UpdateLeverageAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    asset: u32,
+    #[serde(rename = "isCross")]
+    is_cross: bool,
+    leverage: u32,
+}
+
+impl UpdateLeverageAction {
+    pub(crate) fn new(asset: u32, is_cross: bool, leverage: u32) -> Self {
+        UpdateLeverageAction {
+            action_type: "updateLeverage".to_string(),
+            asset,
+            is_cross,
+            leverage,
+        }
+    }
+}
+
+/// Isolated margin update action payload; `ntli` is the USD delta in micro-USD
+#[derive(Debug, Serialize)]
+pub(crate) struct UpdateIsolatedMarginAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    asset: u32,
+    #[serde(rename = "isBuy")]
+    is_buy: bool,
+    ntli: i64,
+}
+
+impl UpdateIsolatedMarginAction {
+    pub(crate) fn new(asset: u32, ntli: i64) -> Self {
+        UpdateIsolatedMarginAction {
+            action_type: "updateIsolatedMargin".to_string(),
+            asset,
+            // Ignored by the exchange for one-way positions but part of the signed payload
+            is_buy: true,
+            ntli,
+        }
+    }
+}
+
+/// Exchange-side TWAP order action payload
+#[derive(Debug, Serialize)]
+pub(crate) struct TwapOrderAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    twap: TwapWire,
+}
+
+#[derive(Debug, Serialize)]
+struct TwapWire {
+    a: u32,
+    b: bool,
+    s: String,
+    r: bool,
+    /// Duration in minutes
+    m: u32,
+    /// Randomize slice timing and size
+    t: bool,
+}
+
+impl TwapOrderAction {
+    pub(crate) fn new(
+        asset: u32,
+        is_buy: bool,
+        sz: f64,
+        reduce_only: bool,
+        minutes: u32,
+        randomize: bool,
+    ) -> Self {
+        TwapOrderAction {
+            action_type: "twapOrder".to_string(),
+            twap: TwapWire {
+                a: asset,
+                b: is_buy,
+                s: float_to_wire(sz),
+                r: reduce_only,
+                m: minutes,
+                t: randomize,
+            },
+        }
+    }
+}
+
+/// TWAP cancel action payload
+#[derive(Debug, Serialize)]
+pub(crate) struct TwapCancelAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    a: u32,
+    t: u64,
+}
+
+impl TwapCancelAction {
+    pub(crate) fn new(asset: u32, twap_id: u64) -> Self {
+        TwapCancelAction {
+            action_type: "twapCancel".to_string(),
+            a: asset,
+            t: twap_id,
+        }
+    }
+}
+
+fn hyperliquid_chain(is_mainnet: bool) -> String {
+    if is_mainnet { "Mainnet" } else { "Testnet" }.to_string()
+}
+
+fn signature_chain_id() -> String {
+    format!("{:#x}", SIGNATURE_CHAIN_ID)
+}
+
+/// USDC transfer between perp accounts (`usdSend`)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsdSendAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    destination: String,
+    amount: String,
+    time: u64,
+}
+
+impl UsdSendAction {
+    pub(crate) fn new(destination: &str, amount: f64, time: u64, is_mainnet: bool) -> Self {
+        UsdSendAction {
+            action_type: "usdSend".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            destination: destination.to_string(),
+            amount: float_to_wire(amount),
+            time,
+        }
+    }
+}
+
+impl UserSignedAction for UsdSendAction {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:UsdSend(string hyperliquidChain,string destination,string amount,uint64 time)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::String(&self.destination),
+            TypedValue::String(&self.amount),
+            TypedValue::Uint64(self.time),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.time
+    }
+}
+
+/// USDC withdrawal to Arbitrum through the bridge (`withdraw3`)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WithdrawAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    destination: String,
+    amount: String,
+    time: u64,
+}
+
+impl WithdrawAction {
+    pub(crate) fn new(destination: &str, amount: f64, time: u64, is_mainnet: bool) -> Self {
+        WithdrawAction {
+            action_type: "withdraw3".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            destination: destination.to_string(),
+            amount: float_to_wire(amount),
+            time,
+        }
+    }
+}
+
+impl UserSignedAction for WithdrawAction {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:Withdraw(string hyperliquidChain,string destination,string amount,uint64 time)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::String(&self.destination),
+            TypedValue::String(&self.amount),
+            TypedValue::Uint64(self.time),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.time
+    }
+}
+
+/// Spot token transfer (`spotSend`); `token` is `NAME:tokenId`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SpotSendAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    destination: String,
+    token: String,
+    amount: String,
+    time: u64,
+}
+
+impl SpotSendAction {
+    pub(crate) fn new(
+        destination: &str,
+        token: &str,
+        amount: f64,
+        time: u64,
+        is_mainnet: bool,
+    ) -> Self {
+        SpotSendAction {
+            action_type: "spotSend".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            destination: destination.to_string(),
+            token: token.to_string(),
+            amount: float_to_wire(amount),
+            time,
+        }
+    }
+}
+
+impl UserSignedAction for SpotSendAction {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:SpotSend(string hyperliquidChain,string destination,string token,string amount,uint64 time)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::String(&self.destination),
+            TypedValue::String(&self.token),
+            TypedValue::String(&self.amount),
+            TypedValue::Uint64(self.time),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.time
+    }
+}
+
+/// USDC transfer between the spot and perp wallets (`usdClassTransfer`)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UsdClassTransferAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    amount: String,
+    to_perp: bool,
+    nonce: u64,
+}
+
+impl UsdClassTransferAction {
+    /// When acting for a vault or sub-account, its address is appended to the amount
+    pub(crate) fn new(
+        amount: f64,
+        to_perp: bool,
+        nonce: u64,
+        vault_address: Option<&str>,
+        is_mainnet: bool,
+    ) -> Self {
+        let mut amount = float_to_wire(amount);
+        if let Some(vault) = vault_address {
+            amount = format!("{} subaccount:{}", amount, vault);
+        }
+        UsdClassTransferAction {
+            action_type: "usdClassTransfer".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            amount,
+            to_perp,
+            nonce,
+        }
+    }
+}
+
+impl UserSignedAction for UsdClassTransferAction {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:UsdClassTransfer(string hyperliquidChain,string amount,bool toPerp,uint64 nonce)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::String(&self.amount),
+            TypedValue::Bool(self.to_perp),
+            TypedValue::Uint64(self.nonce),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// USDC transfer between the master account and a sub-account; `usd` is in micro-USD
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SubAccountTransferAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    sub_account_user: String,
+    is_deposit: bool,
+    usd: u64,
+}
+
+impl SubAccountTransferAction {
+    pub(crate) fn new(sub_account_user: &str, is_deposit: bool, usd: u64) -> Self {
+        SubAccountTransferAction {
+            action_type: "subAccountTransfer".to_string(),
+            sub_account_user: sub_account_user.to_string(),
+            is_deposit,
+            usd,
+        }
+    }
+}
+
+/// USDC deposit into or withdrawal from a vault; `usd` is in micro-USD
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VaultTransferAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    vault_address: String,
+    is_deposit: bool,
+    usd: u64,
+}
+
+impl VaultTransferAction {
+    pub(crate) fn new(vault_address: &str, is_deposit: bool, usd: u64) -> Self {
+        VaultTransferAction {
+            action_type: "vaultTransfer".to_string(),
+            vault_address: vault_address.to_string(),
+            is_deposit,
+            usd,
+        }
+    }
+}
+
+/// Authorize an agent (API wallet) to sign L1 actions for this account (`approveAgent`)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApproveAgentAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    agent_address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    agent_name: Option<String>,
+    nonce: u64,
+    #[serde(skip)]
+    agent: Address,
+}
+
+impl ApproveAgentAction {
+    pub(crate) fn new(
+        agent_address: &str,
+        agent_name: Option<&str>,
+        nonce: u64,
+        is_mainnet: bool,
+    ) -> Result<Self, DexError> {
+        Ok(ApproveAgentAction {
+            action_type: "approveAgent".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            agent_address: agent_address.to_lowercase(),
+            agent_name: agent_name.map(str::to_string),
+            nonce,
+            agent: parse_address(agent_address)?,
+        })
+    }
+}
+
+impl UserSignedAction for ApproveAgentAction {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:ApproveAgent(string hyperliquidChain,address agentAddress,string agentName,uint64 nonce)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::Address(self.agent),
+            // An unnamed agent is signed with an empty name and sent without one
+            TypedValue::String(self.agent_name.as_deref().unwrap_or("")),
+            TypedValue::Uint64(self.nonce),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Allow a builder to charge up to `maxFeeRate` (e.g. `"0.001%"`) on this user's orders
+/// (`approveBuilderFee`)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ApproveBuilderFeeAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    max_fee_rate: String,
+    builder: String,
+    nonce: u64,
+    #[serde(skip)]
+    builder_address: Address,
+}
+
+impl ApproveBuilderFeeAction {
+    pub(crate) fn new(
+        builder: &str,
+        max_fee_rate: &str,
+        nonce: u64,
+        is_mainnet: bool,
+    ) -> Result<Self, DexError> {
+        Ok(ApproveBuilderFeeAction {
+            action_type: "approveBuilderFee".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            max_fee_rate: max_fee_rate.to_string(),
+            builder: builder.to_lowercase(),
+            nonce,
+            builder_address: parse_address(builder)?,
+        })
+    }
+}
+
+impl UserSignedAction for ApproveBuilderFeeAction {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:ApproveBuilderFee(string hyperliquidChain,string maxFeeRate,address builder,uint64 nonce)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::String(&self.max_fee_rate),
+            TypedValue::Address(self.builder_address),
+            TypedValue::Uint64(self.nonce),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Stake to (or unstake from) a validator (`tokenDelegate`); `wei` is in HYPE wei
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TokenDelegateAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    validator: String,
+    wei: u64,
+    is_undelegate: bool,
+    nonce: u64,
+    #[serde(skip)]
+    validator_address: Address,
+}
+
+impl TokenDelegateAction {
+    pub(crate) fn new(
+        validator: &str,
+        wei: u64,
+        is_undelegate: bool,
+        nonce: u64,
+        is_mainnet: bool,
+    ) -> Result<Self, DexError> {
+        Ok(TokenDelegateAction {
+            action_type: "tokenDelegate".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            validator: validator.to_lowercase(),
+            wei,
+            is_undelegate,
+            nonce,
+            validator_address: parse_address(validator)?,
+        })
+    }
+}
+
+impl UserSignedAction for TokenDelegateAction {
+    const EIP712_TYPE: &'static str = "HyperliquidTransaction:TokenDelegate(string hyperliquidChain,address validator,uint64 wei,bool isUndelegate,uint64 nonce)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::Address(self.validator_address),
+            TypedValue::Uint64(self.wei),
+            TypedValue::Bool(self.is_undelegate),
+            TypedValue::Uint64(self.nonce),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Move HYPE from the spot balance into staking (`cDeposit`)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CDepositAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    wei: u64,
+    nonce: u64,
+}
+
+impl CDepositAction {
+    pub(crate) fn new(wei: u64, nonce: u64, is_mainnet: bool) -> Self {
+        CDepositAction {
+            action_type: "cDeposit".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            wei,
+            nonce,
+        }
+    }
+}
+
+impl UserSignedAction for CDepositAction {
+    const EIP712_TYPE: &'static str =
+        "HyperliquidTransaction:CDeposit(string hyperliquidChain,uint64 wei,uint64 nonce)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::Uint64(self.wei),
+            TypedValue::Uint64(self.nonce),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
+/// Move undelegated HYPE from staking back to the spot balance (`cWithdraw`)
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CWithdrawAction {
+    #[serde(rename = "type")]
+    action_type: String,
+    signature_chain_id: String,
+    hyperliquid_chain: String,
+    wei: u64,
+    nonce: u64,
+}
+
+impl CWithdrawAction {
+    pub(crate) fn new(wei: u64, nonce: u64, is_mainnet: bool) -> Self {
+        CWithdrawAction {
+            action_type: "cWithdraw".to_string(),
+            signature_chain_id: signature_chain_id(),
+            hyperliquid_chain: hyperliquid_chain(is_mainnet),
+            wei,
+            nonce,
         }
     }
 }
 
+impl UserSignedAction for CWithdrawAction {
+    const EIP712_TYPE: &'static str =
+        "HyperliquidTransaction:CWithdraw(string hyperliquidChain,uint64 wei,uint64 nonce)";
+
+    fn typed_values(&self) -> Vec<TypedValue<'_>> {
+        vec![
+            TypedValue::String(&self.hyperliquid_chain),
+            TypedValue::Uint64(self.wei),
+            TypedValue::Uint64(self.nonce),
+        ]
+    }
+
+    fn nonce(&self) -> u64 {
+        self.nonce
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use dex_rs_types::{price, qty, Tif};
+    use dex_rs_types::{price, qty, Tif, Trigger};
 
     const TEST_PRIVATE_KEY: &str =
         "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
 
+    /// Key used by the reference Python SDK signing tests
+    const SDK_TEST_KEY: &str = "0x0123456789012345678901234567890123456789012345678901234567890123";
+
+    #[derive(Serialize)]
+    struct DummyAction {
+        #[serde(rename = "type")]
+        action_type: &'static str,
+        num: u64,
+    }
+
+    fn dummy_action() -> DummyAction {
+        // float_to_int_for_hashing(1000) in the Python SDK
+        DummyAction {
+            action_type: "dummy",
+            num: 100_000_000_000,
+        }
+    }
+
+    fn single_order(req: &OrderReq, asset_index: u32, cloid: Option<&str>) -> OrderAction {
+        OrderAction::new(vec![Order::from_req(req, asset_index, cloid)], Grouping::Na)
+    }
+
+    fn sdk_order_req(px: f64, sz: f64, tif: Tif) -> OrderReq {
+        OrderReq {
+            coin: "ETH".to_string(),
+            is_buy: true,
+            px: price(px),
+            qty: qty(sz),
+            tif,
+            reduce_only: false,
+            cloid: None,
+            trigger: None,
+        }
+    }
+
     #[test]
     fn test_signer_creation() {
         let signer = HlSigner::from_hex_key(TEST_PRIVATE_KEY).unwrap();
@@ -161,9 +1094,10 @@ mod tests {
             tif: Tif::Gtc,
             reduce_only: false,
             cloid: None,
+            trigger: None,
         };
 
-        let action = OrderAction::from_req(&order_req, 0, "test_cloid_123");
+        let action = single_order(&order_req, 0, Some("0x00000000000000000000000000000001"));
 
         assert_eq!(action.action_type, "order");
         assert_eq!(action.grouping, "na");
@@ -171,12 +1105,18 @@ mod tests {
 
         let order = &action.orders[0];
         assert_eq!(order.a, 0);
-        assert_eq!(order.b, true);
+        assert!(order.b);
         assert_eq!(order.p, "50000");
         assert_eq!(order.s, "0.001");
-        assert_eq!(order.r, false);
-        assert_eq!(order.t.limit.tif, "Gtc");
-        assert_eq!(order.c, "test_cloid_123");
+        assert!(!order.r);
+        assert_eq!(
+            serde_json::to_value(&order.t).unwrap()["limit"]["tif"],
+            "Gtc"
+        );
+        assert_eq!(
+            order.c.as_deref(),
+            Some("0x00000000000000000000000000000001")
+        );
     }
 
     #[test]
@@ -192,60 +1132,741 @@ mod tests {
                 tif,
                 reduce_only: false,
                 cloid: None,
+                trigger: None,
             };
 
-            let action = OrderAction::from_req(&order_req, 0, "cloid");
-            assert_eq!(action.orders[0].t.limit.tif, expected);
+            let action = single_order(&order_req, 0, None);
+            let t = serde_json::to_value(&action.orders[0].t).unwrap();
+            assert_eq!(t["limit"]["tif"], expected);
         }
     }
 
     #[test]
-    fn test_messagepack_serialization() {
-        let order_req = OrderReq {
-            coin: "BTC".to_string(),
+    fn test_order_wire_omits_missing_cloid() {
+        let action = single_order(&sdk_order_req(100.0, 100.0, Tif::Gtc), 1, None);
+        let json = serde_json::to_value(&action).unwrap();
+
+        assert_eq!(json["type"], "order");
+        assert_eq!(json["orders"][0]["t"]["limit"]["tif"], "Gtc");
+        assert!(json["orders"][0].get("c").is_none());
+    }
+
+    #[test]
+    fn test_cloid_to_wire() {
+        assert_eq!(
+            cloid_to_wire("0x0000000000000000000000000000ABCD").unwrap(),
+            "0x0000000000000000000000000000abcd"
+        );
+        assert_eq!(
+            cloid_to_wire("1701234567890123456_42").unwrap(),
+            "0x179bffd372b4bac0000000000000002a"
+        );
+        assert!(cloid_to_wire("my_order").is_err());
+        assert!(cloid_to_wire("0x1234").is_err());
+    }
+
+    #[test]
+    fn test_action_hash_matches_reference() {
+        // ETH (asset 4) 0.0147 @ 1670.1 IOC, from the Python SDK phantom agent test
+        let action = single_order(&sdk_order_req(1670.1, 0.0147, Tif::Ioc), 4, None);
+        let hash = action_hash(&action, 1677777606040, None).unwrap();
+
+        assert_eq!(
+            format!("{:#x}", hash),
+            "0x0fcbeda5ae3c4950a548021552a4fea2226858c4453571bf3f24ba017eac2908"
+        );
+    }
+
+    #[test]
+    fn test_trigger_order_wire() {
+        let mut req = sdk_order_req(100.0, 100.0, Tif::Gtc);
+        req.trigger = Some(Trigger::stop_market(price(103.0)));
+        let json = serde_json::to_value(Order::from_req(&req, 1, None)).unwrap();
+
+        assert_eq!(
+            json["t"],
+            serde_json::json!({"trigger": {"isMarket": true, "triggerPx": "103", "tpsl": "sl"}})
+        );
+
+        req.trigger = Some(Trigger::take_profit_limit(price(120.5)));
+        let json = serde_json::to_value(Order::from_req(&req, 1, None)).unwrap();
+        assert_eq!(json["t"]["trigger"]["isMarket"], false);
+        assert_eq!(json["t"]["trigger"]["triggerPx"], "120.5");
+        assert_eq!(json["t"]["trigger"]["tpsl"], "tp");
+    }
+
+    #[test]
+    fn test_tpsl_grouping_wire() {
+        let entry = sdk_order_req(100.0, 1.0, Tif::Gtc);
+        let mut stop = sdk_order_req(90.0, 1.0, Tif::Gtc);
+        stop.is_buy = false;
+        stop.reduce_only = true;
+        stop.trigger = Some(Trigger::stop_market(price(90.0)));
+
+        let orders = vec![
+            Order::from_req(&entry, 1, None),
+            Order::from_req(&stop, 1, None),
+        ];
+        let action = OrderAction::new(orders, Grouping::NormalTpsl);
+        assert_eq!(action.grouping, "normalTpsl");
+
+        let action = OrderAction::new(Vec::new(), Grouping::PositionTpsl);
+        assert_eq!(action.grouping, "positionTpsl");
+    }
+
+    #[test]
+    fn test_batch_order_action() {
+        let orders = vec![
+            Order::from_req(&sdk_order_req(100.0, 1.0, Tif::Alo), 1, None),
+            Order::from_req(&sdk_order_req(99.5, 2.0, Tif::Alo), 1, None),
+            Order::from_req(&sdk_order_req(99.0, 3.0, Tif::Alo), 1, None),
+        ];
+        let action = OrderAction::new(orders, Grouping::Na);
+        let json = serde_json::to_value(&action).unwrap();
+
+        assert_eq!(json["grouping"], "na");
+        let orders = json["orders"].as_array().unwrap();
+        assert_eq!(orders.len(), 3);
+        assert_eq!(orders[1]["p"], "99.5");
+        assert_eq!(orders[2]["s"], "3");
+    }
+
+    #[test]
+    fn test_batch_modify_action_layout() {
+        let req = sdk_order_req(101.0, 2.0, Tif::Alo);
+        let action = BatchModifyAction::new(vec![
+            Modify::new(OidOrCloid::Oid(12345), Order::from_req(&req, 1, None)),
+            Modify::new(
+                OidOrCloid::Cloid("0x00000000000000000000000000000001".into()),
+                Order::from_req(&req, 1, None),
+            ),
+        ]);
+        let json = serde_json::to_value(&action).unwrap();
+
+        assert_eq!(json["type"], "batchModify");
+        assert_eq!(json["modifies"][0]["oid"], 12345);
+        assert_eq!(
+            json["modifies"][1]["oid"],
+            "0x00000000000000000000000000000001"
+        );
+        assert_eq!(json["modifies"][0]["order"]["p"], "101");
+        assert_eq!(json["modifies"][0]["order"]["t"]["limit"]["tif"], "Alo");
+    }
+
+    #[test]
+    fn test_cancel_action_layout() {
+        let action = CancelAction::new([(1, 12345), (4, 67890)]);
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "cancel",
+                "cancels": [{"a": 1, "o": 12345}, {"a": 4, "o": 67890}]
+            })
+        );
+
+        // msgpack map: {"type": "cancel", "cancels": [{"a": 1, "o": 12345}, ...]}
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert_eq!(&bytes[..7], &[0x82, 0xa4, b't', b'y', b'p', b'e', 0xa6]);
+        assert_eq!(&bytes[21..24], &[0x92, 0x82, 0xa1]);
+    }
+
+    #[test]
+    fn test_cancel_by_cloid_action_layout() {
+        let cloid = "0x00000000000000000000000000000001".to_string();
+        let action = CancelByCloidAction::new([(3, cloid.clone())]);
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "cancelByCloid",
+                "cancels": [{"asset": 3, "cloid": cloid}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_transfer_action_layouts() {
+        let sub = "0x1d9470d4b963f552e6f671a81619d395877bf409";
+        let action = SubAccountTransferAction::new(sub, true, 1_000_000);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({"type": "subAccountTransfer", "subAccountUser": sub, "isDeposit": true, "usd": 1_000_000})
+        );
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert!(key_pos(&bytes, "subAccountUser") < key_pos(&bytes, "isDeposit"));
+        assert!(key_pos(&bytes, "isDeposit") < key_pos(&bytes, "usd"));
+
+        let action = VaultTransferAction::new(sub, false, 5_000_000);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({"type": "vaultTransfer", "vaultAddress": sub, "isDeposit": false, "usd": 5_000_000})
+        );
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert!(key_pos(&bytes, "vaultAddress") < key_pos(&bytes, "isDeposit"));
+    }
+
+    /// Position of a msgpack `fixstr` key within encoded bytes
+    fn key_pos(bytes: &[u8], key: &str) -> usize {
+        let mut needle = vec![0xa0 | key.len() as u8];
+        needle.extend_from_slice(key.as_bytes());
+        bytes
+            .windows(needle.len())
+            .position(|w| w == needle.as_slice())
+            .unwrap_or_else(|| panic!("key {} not found", key))
+    }
+
+    #[test]
+    fn test_schedule_cancel_action_layout() {
+        let action = ScheduleCancelAction::new(Some(1_700_000_000_000));
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({"type": "scheduleCancel", "time": 1_700_000_000_000u64})
+        );
+
+        // Clearing the schedule omits `time` entirely
+        let action = ScheduleCancelAction::new(None);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({"type": "scheduleCancel"})
+        );
+        assert_eq!(rmp_serde::to_vec_named(&action).unwrap()[0], 0x81);
+    }
+
+    #[test]
+    fn test_update_leverage_action_layout() {
+        let action = UpdateLeverageAction::new(4, false, 10);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({"type": "updateLeverage", "asset": 4, "isCross": false, "leverage": 10})
+        );
+
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert_eq!(bytes[0], 0x84);
+        assert!(key_pos(&bytes, "type") < key_pos(&bytes, "asset"));
+        assert!(key_pos(&bytes, "asset") < key_pos(&bytes, "isCross"));
+        assert!(key_pos(&bytes, "isCross") < key_pos(&bytes, "leverage"));
+    }
+
+    #[test]
+    fn test_update_isolated_margin_action_layout() {
+        let action = UpdateIsolatedMarginAction::new(0, -2_500_000);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({"type": "updateIsolatedMargin", "asset": 0, "isBuy": true, "ntli": -2_500_000})
+        );
+
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert_eq!(bytes[0], 0x84);
+        assert!(key_pos(&bytes, "asset") < key_pos(&bytes, "isBuy"));
+        assert!(key_pos(&bytes, "isBuy") < key_pos(&bytes, "ntli"));
+    }
+
+    #[test]
+    fn test_twap_order_action_layout() {
+        let action = TwapOrderAction::new(3, true, 1.5, false, 30, true);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({
+                "type": "twapOrder",
+                "twap": {"a": 3, "b": true, "s": "1.5", "r": false, "m": 30, "t": true}
+            })
+        );
+
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert_eq!(bytes[0], 0x82);
+        assert!(key_pos(&bytes, "type") < key_pos(&bytes, "twap"));
+        let keys: Vec<usize> = ["a", "b", "s", "r", "m", "t"]
+            .iter()
+            .map(|key| key_pos(&bytes, key))
+            .collect();
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn test_twap_cancel_action_layout() {
+        let action = TwapCancelAction::new(3, 4_242);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({"type": "twapCancel", "a": 3, "t": 4_242})
+        );
+
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert_eq!(bytes[0], 0x83);
+        assert!(key_pos(&bytes, "a") < key_pos(&bytes, "t"));
+    }
+
+    const TRANSFER_DESTINATION: &str = "0x5e9ee1089755c3435139848e47e6635505d5a13a";
+    const TRANSFER_TIME: u64 = 1687816341423;
+
+    fn hex(hash: B256) -> String {
+        format!("{:#x}", hash)
+    }
+
+    #[test]
+    fn test_user_signed_action_layout() {
+        let action = UsdSendAction::new(TRANSFER_DESTINATION, 1.0, TRANSFER_TIME, false);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({
+                "type": "usdSend",
+                "signatureChainId": "0x66eee",
+                "hyperliquidChain": "Testnet",
+                "destination": TRANSFER_DESTINATION,
+                "amount": "1",
+                "time": TRANSFER_TIME
+            })
+        );
+
+        let action = UsdClassTransferAction::new(1.5, true, TRANSFER_TIME, None, true);
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({
+                "type": "usdClassTransfer",
+                "signatureChainId": "0x66eee",
+                "hyperliquidChain": "Mainnet",
+                "amount": "1.5",
+                "toPerp": true,
+                "nonce": TRANSFER_TIME
+            })
+        );
+        assert_eq!(action.nonce(), TRANSFER_TIME);
+
+        let action = UsdClassTransferAction::new(
+            1.5,
+            false,
+            TRANSFER_TIME,
+            Some(TRANSFER_DESTINATION),
+            true,
+        );
+        assert_eq!(
+            serde_json::to_value(&action).unwrap()["amount"],
+            format!("1.5 subaccount:{}", TRANSFER_DESTINATION)
+        );
+    }
+
+    #[test]
+    fn test_user_signed_struct_hashes() {
+        let usd_send = UsdSendAction::new(TRANSFER_DESTINATION, 1.0, TRANSFER_TIME, false);
+        assert_eq!(
+            hex(user_signed_struct_hash(&usd_send)),
+            "0xe81f1691f350bb9a59d2b40b3d16c9526a8f93407dd72bcb9ea95e291a5de6da"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&usd_send)),
+            "0xcacf7585cc49ca60c6c5fb3001e226c0ca03c46252c893eea574c78907b7cebe"
+        );
+
+        let withdraw = WithdrawAction::new(TRANSFER_DESTINATION, 1.0, TRANSFER_TIME, false);
+        assert_eq!(
+            hex(user_signed_struct_hash(&withdraw)),
+            "0xbb36a2adff957df448a8f3da0bcb708a2687fdc0644784bce37a1fd28e251a3c"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&withdraw)),
+            "0x8080d06f566813165cec60d85300445133b3439e9c92e10b93ed510372608899"
+        );
+
+        let spot_send = SpotSendAction::new(
+            TRANSFER_DESTINATION,
+            "PURR:0xc4bf3f870c0e9465323c0b6ed28096c2",
+            100.0,
+            TRANSFER_TIME,
+            true,
+        );
+        assert_eq!(
+            hex(user_signed_struct_hash(&spot_send)),
+            "0xb1a4ff80f0392e4203ee39c28d5538491f84b0bafaeafbf09e8e8d526a9a2185"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&spot_send)),
+            "0x7494500ff4732ffc73bce694857265c2ed20b14f0663a73236ade1c1dfc6a06a"
+        );
+
+        let class_transfer = UsdClassTransferAction::new(1.5, true, TRANSFER_TIME, None, true);
+        assert_eq!(
+            hex(user_signed_struct_hash(&class_transfer)),
+            "0xe68fd1554eca350b490aecdda56dfbc0cd728b5ca359e37d06082e3ad5c38620"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&class_transfer)),
+            "0x4c52ead444f7e32ade9fa98f5e61553af06e5b2a054c53eae920002808c0d189"
+        );
+    }
+
+    #[test]
+    fn test_approve_agent_action() {
+        let action =
+            ApproveAgentAction::new(TRANSFER_DESTINATION, Some("bot"), TRANSFER_TIME, false)
+                .unwrap();
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({
+                "type": "approveAgent",
+                "signatureChainId": "0x66eee",
+                "hyperliquidChain": "Testnet",
+                "agentAddress": TRANSFER_DESTINATION,
+                "agentName": "bot",
+                "nonce": TRANSFER_TIME
+            })
+        );
+        assert_eq!(
+            hex(user_signed_struct_hash(&action)),
+            "0x1882af3fdbaaf0bbbc02e47a1df2db20b6be3914e1f8f70f693f9e9f7fa778a1"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&action)),
+            "0xf9ce64c7877e22badd57cfda4ebb4902d781d6f7c032dcee4ea5e5db2f24fc8d"
+        );
+
+        // Unnamed agents omit agentName on the wire but sign an empty string
+        let action =
+            ApproveAgentAction::new(TRANSFER_DESTINATION, None, TRANSFER_TIME, false).unwrap();
+        assert!(serde_json::to_value(&action)
+            .unwrap()
+            .get("agentName")
+            .is_none());
+        assert_eq!(
+            hex(user_signed_struct_hash(&action)),
+            "0x91cc37769bc4e5234763527bbd32c9aff23d5f2508083ab75e6343bbb3557681"
+        );
+
+        assert!(ApproveAgentAction::new("not-an-address", None, TRANSFER_TIME, false).is_err());
+    }
+
+    #[test]
+    fn test_order_action_builder_layout() {
+        let req = OrderReq {
+            coin: "BTC".into(),
             is_buy: true,
-            px: price(50000.0),
-            qty: qty(0.001),
+            px: price(30000.0),
+            qty: qty(0.1),
             tif: Tif::Gtc,
             reduce_only: false,
             cloid: None,
+            trigger: None,
         };
+        let orders = vec![Order::from_req(&req, 0, None)];
 
-        let action = OrderAction::from_req(&order_req, 0, "test_cloid");
-        let user_signed_action = UserSignedAction {
-            action,
-            nonce: 12345,
-        };
+        // Without a builder the action keeps its three keys
+        let action = OrderAction::new(orders, Grouping::Na);
+        let plain = rmp_serde::to_vec_named(&action).unwrap();
+        assert_eq!(plain[0], 0x83);
+
+        let action = action.with_builder(Some(BuilderWire::new(
+            "0x5E9EE1089755C3435139848E47E6635505D5A13A",
+            10,
+        )));
+        assert_eq!(
+            serde_json::to_value(&action).unwrap()["builder"],
+            serde_json::json!({"b": TRANSFER_DESTINATION, "f": 10})
+        );
+
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert_eq!(bytes[0], 0x84);
+        assert!(key_pos(&bytes, "grouping") < key_pos(&bytes, "builder"));
+
+        // The builder map is appended after the unchanged order payload
+        let mut builder = vec![0xa7];
+        builder.extend_from_slice(b"builder");
+        builder.extend_from_slice(&[0x82, 0xa1, b'b', 0xd9, 42]);
+        builder.extend_from_slice(TRANSFER_DESTINATION.as_bytes());
+        builder.extend_from_slice(&[0xa1, b'f', 10]);
+        assert_eq!(&bytes[1..plain.len()], &plain[1..]);
+        assert_eq!(&bytes[plain.len()..], builder.as_slice());
+    }
+
+    #[test]
+    fn test_approve_builder_fee_action() {
+        let action =
+            ApproveBuilderFeeAction::new(TRANSFER_DESTINATION, "0.001%", TRANSFER_TIME, false)
+                .unwrap();
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({
+                "type": "approveBuilderFee",
+                "signatureChainId": "0x66eee",
+                "hyperliquidChain": "Testnet",
+                "maxFeeRate": "0.001%",
+                "builder": TRANSFER_DESTINATION,
+                "nonce": TRANSFER_TIME
+            })
+        );
+
+        let bytes = rmp_serde::to_vec_named(&action).unwrap();
+        assert_eq!(bytes[0], 0x86);
+        assert!(key_pos(&bytes, "hyperliquidChain") < key_pos(&bytes, "maxFeeRate"));
+        assert!(key_pos(&bytes, "maxFeeRate") < key_pos(&bytes, "builder"));
+        assert!(key_pos(&bytes, "builder") < key_pos(&bytes, "nonce"));
 
-        // Should serialize to MessagePack without error
-        let result = rmp_serde::to_vec(&user_signed_action);
-        assert!(result.is_ok());
+        assert_eq!(
+            hex(user_signed_struct_hash(&action)),
+            "0x51b284e7d35277f801a9b9cdb6fc234562f26355f15372c0bea312f3dd0ad119"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&action)),
+            "0x6fd3213e26b415046fd462786424b9baca6f71d406784e01af792e90ee1a14b1"
+        );
+        assert!(ApproveBuilderFeeAction::new("builder", "0.001%", TRANSFER_TIME, false).is_err());
+    }
+
+    #[test]
+    fn test_staking_actions() {
+        let action = TokenDelegateAction::new(
+            TRANSFER_DESTINATION,
+            100_000_000,
+            false,
+            TRANSFER_TIME,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({
+                "type": "tokenDelegate",
+                "signatureChainId": "0x66eee",
+                "hyperliquidChain": "Testnet",
+                "validator": TRANSFER_DESTINATION,
+                "wei": 100_000_000,
+                "isUndelegate": false,
+                "nonce": TRANSFER_TIME
+            })
+        );
+        assert_eq!(
+            hex(user_signed_struct_hash(&action)),
+            "0xa5c9637e2a10e63008660b45dc6e3532b67a1cdfa876782bf5c6483ba32852c4"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&action)),
+            "0x4487d471ab3f2d31c764b74bb5b85d5d76df08f90447f7bae7a5be9d18ff287c"
+        );
+        assert!(TokenDelegateAction::new("validator", 1, false, TRANSFER_TIME, false).is_err());
+
+        let deposit = CDepositAction::new(250_000_000, TRANSFER_TIME, true);
+        assert_eq!(
+            serde_json::to_value(&deposit).unwrap(),
+            serde_json::json!({
+                "type": "cDeposit",
+                "signatureChainId": "0x66eee",
+                "hyperliquidChain": "Mainnet",
+                "wei": 250_000_000,
+                "nonce": TRANSFER_TIME
+            })
+        );
+        assert_eq!(
+            hex(user_signed_struct_hash(&deposit)),
+            "0xd36559db2bbb5c5ca4579319c8ae090830335c6b6e33c86562f9a6a3b2030909"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&deposit)),
+            "0x436478949c7cd888566d96040b1374d867465336f93488a1e3ca02f0da309d03"
+        );
 
-        let msgpack_bytes = result.unwrap();
-        assert!(!msgpack_bytes.is_empty());
+        let withdraw = CWithdrawAction::new(250_000_000, TRANSFER_TIME, true);
+        assert_eq!(
+            serde_json::to_value(&withdraw).unwrap()["type"],
+            "cWithdraw"
+        );
+        assert_eq!(
+            hex(user_signed_struct_hash(&withdraw)),
+            "0x7502de79c1b10e270316dae032a2123f183e67f12940a7621b75ebdeb255e63d"
+        );
+        assert_eq!(
+            hex(user_signed_signing_hash(&withdraw)),
+            "0x9dd0fb3069b67377fd7525d6d85631261bbffe96f9c8a5c804a632ff91137c52"
+        );
     }
 
     #[tokio::test]
-    async fn test_sign_order() {
-        let signer = HlSigner::from_hex_key(TEST_PRIVATE_KEY).unwrap();
+    async fn test_sign_user_signed_action_matches_reference() {
+        let signer = HlSigner::from_hex_key(SDK_TEST_KEY).unwrap();
 
-        let order_req = OrderReq {
-            coin: "BTC".to_string(),
-            is_buy: true,
-            px: price(50000.0),
-            qty: qty(0.001),
-            tif: Tif::Gtc,
-            reduce_only: false,
-            cloid: None,
-        };
+        let usd_send = UsdSendAction::new(TRANSFER_DESTINATION, 1.0, TRANSFER_TIME, false);
+        let sig = signer.sign_user_signed_action(&usd_send).await.unwrap();
+        assert_eq!(
+            sig.r,
+            "0x637b37dd731507cdd24f46532ca8ba6eec616952c56218baeff04144e4a77073"
+        );
+        assert_eq!(
+            sig.s,
+            "0x11a6a24900e6e314136d2592e2f8d502cd89b7c15b198e1bee043c9589f9fad7"
+        );
+        assert_eq!(sig.v, 27);
 
-        let result = signer.sign_order(&order_req, 12345, 0, "test_cloid").await;
-        assert!(result.is_ok());
+        let withdraw = WithdrawAction::new(TRANSFER_DESTINATION, 1.0, TRANSFER_TIME, false);
+        let sig = signer.sign_user_signed_action(&withdraw).await.unwrap();
+        assert_eq!(
+            sig.r,
+            "0x8363524c799e90ce9bc41022f7c39b4e9bdba786e5f9c72b20e43e1462c37cf9"
+        );
+        assert_eq!(
+            sig.s,
+            "0x58b1411a775938b83e29182e8ef74975f9054c8e97ebf5ec2dc8d51bfc893881"
+        );
+        assert_eq!(sig.v, 28);
+    }
 
-        let signature = result.unwrap();
-        // Should be hex string starting with 0x
-        assert!(signature.starts_with("0x"));
-        // Should be 132 characters (0x + 130 hex chars = 65 bytes: 32 + 32 + 1 for r,s,v)
-        assert_eq!(signature.len(), 132);
+    #[tokio::test]
+    async fn test_sign_l1_action_matches_reference() {
+        let signer = HlSigner::from_hex_key(SDK_TEST_KEY).unwrap();
+        let action = dummy_action();
+
+        let mainnet = signer.sign_l1_action(&action, 0, None, true).await.unwrap();
+        assert_eq!(
+            mainnet.r,
+            "0x53749d5b30552aeb2fca34b530185976545bb22d0b3ce6f62e31be961a59298"
+        );
+        assert_eq!(
+            mainnet.s,
+            "0x755c40ba9bf05223521753995abb2f73ab3229be8ec921f350cb447e384d8ed8"
+        );
+        assert_eq!(mainnet.v, 27);
+
+        let testnet = signer
+            .sign_l1_action(&action, 0, None, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            testnet.r,
+            "0x542af61ef1f429707e3c76c5293c80d01f74ef853e34b76efffcb57e574f9510"
+        );
+        assert_eq!(
+            testnet.s,
+            "0x17b8b32f086e8cdede991f1e2c529f5dd5297cbe8128500e00cbaf766204a613"
+        );
+        assert_eq!(testnet.v, 28);
+    }
+
+    #[tokio::test]
+    async fn test_sign_l1_action_with_vault_matches_reference() {
+        let signer = HlSigner::from_hex_key(SDK_TEST_KEY).unwrap();
+        let vault = Some("0x1719884eb866cb12b2287399b15f7db5e7d775ea");
+
+        let mainnet = signer
+            .sign_l1_action(&dummy_action(), 0, vault, true)
+            .await
+            .unwrap();
+        assert_eq!(
+            mainnet.r,
+            "0x3c548db75e479f8012acf3000ca3a6b05606bc2ec0c29c50c515066a326239"
+        );
+        assert_eq!(
+            mainnet.s,
+            "0x4d402be7396ce74fbba3795769cda45aec00dc3125a984f2a9f23177b190da2c"
+        );
+        assert_eq!(mainnet.v, 28);
+
+        let testnet = signer
+            .sign_l1_action(&dummy_action(), 0, vault, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            testnet.r,
+            "0xe281d2fb5c6e25ca01601f878e4d69c965bb598b88fac58e475dd1f5e56c362b"
+        );
+        assert_eq!(
+            testnet.s,
+            "0x7ddad27e9a238d045c035bc606349d075d5c5cd00a6cd1da23ab5c39d4ef0f60"
+        );
+        assert_eq!(testnet.v, 27);
+    }
+
+    #[tokio::test]
+    async fn test_sign_order_matches_reference() {
+        let signer = HlSigner::from_hex_key(SDK_TEST_KEY).unwrap();
+        let action = single_order(&sdk_order_req(100.0, 100.0, Tif::Gtc), 1, None);
+
+        let mainnet = signer.sign_l1_action(&action, 0, None, true).await.unwrap();
+        assert_eq!(
+            mainnet.r,
+            "0xd65369825a9df5d80099e513cce430311d7d26ddf477f5b3a33d2806b100d78e"
+        );
+        assert_eq!(
+            mainnet.s,
+            "0x2b54116ff64054968aa237c20ca9ff68000f977c93289157748a3162b6ea940e"
+        );
+        assert_eq!(mainnet.v, 28);
+
+        let testnet = signer
+            .sign_l1_action(&action, 0, None, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            testnet.r,
+            "0x82b2ba28e76b3d761093aaded1b1cdad4960b3af30212b343fb2e6cdfa4e3d54"
+        );
+        assert_eq!(
+            testnet.s,
+            "0x6b53878fc99d26047f4d7e8c90eb98955a109f44209163f52d8dc4278cbbd9f5"
+        );
+        assert_eq!(testnet.v, 27);
+    }
+
+    #[tokio::test]
+    async fn test_sign_tpsl_order_matches_reference() {
+        let signer = HlSigner::from_hex_key(SDK_TEST_KEY).unwrap();
+        let mut req = sdk_order_req(100.0, 100.0, Tif::Gtc);
+        req.trigger = Some(Trigger::stop_market(price(103.0)));
+        let action = single_order(&req, 1, None);
+
+        let mainnet = signer.sign_l1_action(&action, 0, None, true).await.unwrap();
+        assert_eq!(
+            mainnet.r,
+            "0x98343f2b5ae8e26bb2587daad3863bc70d8792b09af1841b6fdd530a2065a3f9"
+        );
+        assert_eq!(
+            mainnet.s,
+            "0x6b5bb6bb0633b710aa22b721dd9dee6d083646a5f8e581a20b545be6c1feb405"
+        );
+        assert_eq!(mainnet.v, 27);
+
+        let testnet = signer
+            .sign_l1_action(&action, 0, None, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            testnet.r,
+            "0x971c554d917c44e0e1b6cc45d8f9404f32172a9d3b3566262347d0302896a2e4"
+        );
+        assert_eq!(
+            testnet.s,
+            "0x206257b104788f80450f8e786c329daa589aa0b32ba96948201ae556d5637eac"
+        );
+        assert_eq!(testnet.v, 28);
+    }
+
+    #[tokio::test]
+    async fn test_sign_order_with_cloid_matches_reference() {
+        let signer = HlSigner::from_hex_key(SDK_TEST_KEY).unwrap();
+        let action = single_order(
+            &sdk_order_req(100.0, 100.0, Tif::Gtc),
+            1,
+            Some("0x00000000000000000000000000000001"),
+        );
+
+        let mainnet = signer.sign_l1_action(&action, 0, None, true).await.unwrap();
+        assert_eq!(
+            mainnet.r,
+            "0x41ae18e8239a56cacbc5dad94d45d0b747e5da11ad564077fcac71277a946e3"
+        );
+        assert_eq!(
+            mainnet.s,
+            "0x3c61f667e747404fe7eea8f90ab0e76cc12ce60270438b2058324681a00116da"
+        );
+        assert_eq!(mainnet.v, 27);
+
+        let testnet = signer
+            .sign_l1_action(&action, 0, None, false)
+            .await
+            .unwrap();
+        assert_eq!(
+            testnet.r,
+            "0xeba0664bed2676fc4e5a743bf89e5c7501aa6d870bdb9446e122c9466c5cd16d"
+        );
+        assert_eq!(
+            testnet.s,
+            "0x7f3e74825c9114bc59086f1eebea2928c190fdfbfde144827cb02b85bbe90988"
+        );
+        assert_eq!(testnet.v, 28);
     }
 }
@@ -1,17 +1,336 @@
+use crate::http::{check_book_aggregation, exchange_response};
 use bytes::Bytes;
-use dex_rs_core::traits::{FillEvent, OrderEvent, StreamEvent, StreamKind};
-use dex_rs_core::{ws::WsTransport, DexError};
-use dex_rs_types::{price, qty, OrderBook, OrderBookLevel, Side, Trade};
+use dex_rs_core::traits::{
+    ActiveAssetDataEvent, ConnectionState, FillEvent, FundingEvent, LedgerUpdateEvent,
+    LiquidationEvent, OrderEvent, Position, StreamEvent, StreamKind, SubscriptionHandle,
+    WebData2Event,
+};
+use dex_rs_core::{
+    ws::{WsConnection, WsTransport},
+    DexError,
+};
+use dex_rs_types::{
+    price, qty, AllMids, AssetCtx, BookAggregation, Candle, CandleInterval, Leverage, OpenOrder,
+    OrderBook, OrderBookLevel, Side, SpotAssetCtx, Trade, TwapHistoryEntry,
+};
+use rand::Rng;
 use serde::Deserialize;
-use serde_json::json;
+use serde_json::{json, Value};
 use simd_json::prelude::*;
 use simd_json::BorrowedValue;
-use tokio::sync::mpsc;
-use tokio::time::{sleep, Duration};
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use tokio::sync::{mpsc, oneshot, watch};
+use tokio::time::{interval_at, sleep, timeout, Duration, Instant, MissedTickBehavior};
 
+/// The server drops sockets idle for 60s, so ping well inside that
+const PING_INTERVAL: Duration = Duration::from_secs(20);
+/// A ping still unanswered at the next interval marks the connection dead
+const PONG_TIMEOUT: Duration = PING_INTERVAL;
+/// No subscription data for this long, while a market data stream that ticks
+/// continuously is subscribed, marks the connection dead
+const STALE_TIMEOUT: Duration = Duration::from_secs(60);
+/// A connection that stays up this long, or delivers data, resets the retry budget
+const STABLE_CONNECTION: Duration = Duration::from_secs(30);
+/// Events kept for replay to late joiners of a snapshot stream such as fills
+const REPLAY_HISTORY_LIMIT: usize = 2000;
+/// How long a `post` request waits for its response
+const POST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How the shared connection retries after it drops or fails to connect
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectPolicy {
+    /// Consecutive failed attempts before giving up; `None` retries forever
+    pub max_retries: Option<u32>,
+    /// Delay before the first retry, doubled on each further attempt
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Random extra delay as a fraction of the backoff (0.25 adds up to 25%)
+    pub jitter: f64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: Some(10),
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            jitter: 0.25,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Default delays, retrying forever
+    pub fn infinite() -> Self {
+        Self {
+            max_retries: None,
+            ..Self::default()
+        }
+    }
+
+    /// Whether `attempt` (1-based) exceeds the retry budget
+    fn exhausted(&self, attempt: u32) -> bool {
+        self.max_retries.is_some_and(|max| attempt > max)
+    }
+
+    /// Backoff before retry `attempt` (1-based), with random jitter
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2_u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        let jitter = if self.jitter > 0.0 {
+            rand::thread_rng().gen_range(0.0..=self.jitter)
+        } else {
+            0.0
+        };
+        delay.mul_f64(1.0 + jitter)
+    }
+}
+
+/// Hyperliquid WebSocket client; all subscriptions share one connection
 pub struct HlWs<T: WsTransport + Clone + 'static> {
     txp: T,
     url: String,
+    policy: ReconnectPolicy,
+    /// Connection manager, started on first subscribe
+    manager: Mutex<Option<Manager>>,
+    next_id: AtomicU64,
+    /// Aggregation (`None` for full depth) and handle count of each coin's L2 book
+    books: Arc<BookClaims>,
+}
+
+type BookClaims = Mutex<HashMap<String, (Option<BookAggregation>, usize)>>;
+
+/// Channels to a running connection manager
+#[derive(Clone)]
+struct Manager {
+    commands: mpsc::UnboundedSender<Command>,
+}
+
+enum Command {
+    Subscribe(Subscriber),
+    Unsubscribe(u64),
+    Post(Post),
+}
+
+/// An action or info request sent as a `post` message, answered by id
+struct Post {
+    id: u64,
+    message: Bytes,
+    reply: oneshot::Sender<Result<Value, DexError>>,
+}
+
+/// A consumer of one subscription on the shared connection
+struct Subscriber {
+    id: u64,
+    kind: StreamKind,
+    /// Body of the `subscribe` message, e.g. `{"type": "trades", "coin": "BTC"}`
+    subscription: Value,
+    /// `subscription` as text; subscribers with the same topic share one server subscription
+    topic: String,
+    /// Channel the exchange tags this subscription's messages with
+    channel: &'static str,
+    /// Coin, user, or user and coin the subscription is scoped to
+    key: Option<String>,
+    out: mpsc::UnboundedSender<StreamEvent>,
+    /// State reported by the subscription's handle
+    state: watch::Sender<ConnectionState>,
+    /// Whether the server acknowledged the subscription on the current connection
+    live: bool,
+    /// Whether the subscription has been live on some connection
+    connected: bool,
+}
+
+impl Subscriber {
+    fn new(
+        id: u64,
+        kind: StreamKind,
+        subscription: Value,
+        out: mpsc::UnboundedSender<StreamEvent>,
+    ) -> (Self, watch::Receiver<ConnectionState>) {
+        let (state, state_rx) = watch::channel(ConnectionState::Connecting);
+        let sub = Self {
+            id,
+            kind,
+            channel: channel_name(kind),
+            key: subscription_key(&subscription),
+            topic: subscription.to_string(),
+            subscription,
+            out,
+            state,
+            live: false,
+            connected: false,
+        };
+        (sub, state_rx)
+    }
+
+    /// Mark the subscription live on the current connection
+    fn go_live(&mut self) {
+        // A resubscribed stream may have missed data while disconnected
+        let event = if self.connected {
+            StreamEvent::Resubscribed
+        } else {
+            StreamEvent::Connected
+        };
+        self.live = true;
+        self.connected = true;
+        self.state.send_replace(ConnectionState::Connected);
+        let _ = self.out.send(event);
+    }
+
+    fn matches(&self, channel: &str, key: Option<&str>) -> bool {
+        // Spot coins' asset contexts arrive on their own channel
+        let spot_ctx =
+            matches!(self.kind, StreamKind::ActiveAssetCtx) && channel == "activeSpotAssetCtx";
+        (self.channel == channel || spot_ctx)
+            && match (self.key.as_deref(), key) {
+                (Some(ours), Some(theirs)) => ours == theirs,
+                _ => true,
+            }
+    }
+}
+
+/// Channel name of the messages for a stream kind
+fn channel_name(kind: StreamKind) -> &'static str {
+    match kind {
+        StreamKind::Bbo => "bbo",
+        StreamKind::Trades => "trades",
+        StreamKind::L2Book | StreamKind::L2BookAggregated(_) => "l2Book",
+        StreamKind::Orders => "orderUpdates",
+        StreamKind::Fills => "userFills",
+        StreamKind::TwapSliceFills => "userTwapSliceFills",
+        StreamKind::AllMids => "allMids",
+        StreamKind::Candle(_) => "candle",
+        StreamKind::Notification => "notification",
+        StreamKind::WebData2 => "webData2",
+        StreamKind::UserEvents => "user",
+        StreamKind::UserFundings => "userFundings",
+        StreamKind::UserNonFundingLedgerUpdates => "userNonFundingLedgerUpdates",
+        StreamKind::ActiveAssetCtx => "activeAssetCtx",
+        StreamKind::ActiveAssetData => "activeAssetData",
+        StreamKind::UserTwapHistory => "userTwapHistory",
+    }
+}
+
+/// How a subscriber joining a subscription the server already serves catches up,
+/// since the server only sends snapshots on subscribe
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Replay {
+    /// Nothing to replay; the stream only carries new events
+    Nothing,
+    /// Every message carries the full state, so the latest one is replayed
+    Latest,
+    /// The snapshot message and the events since then are replayed
+    History,
+}
+
+fn replay(kind: StreamKind) -> Replay {
+    match kind {
+        StreamKind::L2Book
+        | StreamKind::L2BookAggregated(_)
+        | StreamKind::Bbo
+        | StreamKind::AllMids
+        | StreamKind::Candle(_)
+        | StreamKind::WebData2
+        | StreamKind::ActiveAssetCtx
+        | StreamKind::ActiveAssetData => Replay::Latest,
+        StreamKind::Fills
+        | StreamKind::TwapSliceFills
+        | StreamKind::UserFundings
+        | StreamKind::UserNonFundingLedgerUpdates
+        | StreamKind::UserTwapHistory => Replay::History,
+        StreamKind::Trades
+        | StreamKind::Orders
+        | StreamKind::UserEvents
+        | StreamKind::Notification => Replay::Nothing,
+    }
+}
+
+/// Market data streams that update continuously; user streams, candles of
+/// illiquid coins and the like can be silent for long stretches
+fn is_continuous(kind: StreamKind) -> bool {
+    matches!(
+        kind,
+        StreamKind::Trades
+            | StreamKind::L2Book
+            | StreamKind::L2BookAggregated(_)
+            | StreamKind::Bbo
+            | StreamKind::AllMids
+    )
+}
+
+/// Drop one handle's claim on `coin`'s L2 book
+fn release_book(books: &BookClaims, coin: &str) {
+    let mut books = books.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, count)) = books.get_mut(coin) {
+        *count -= 1;
+        if *count == 0 {
+            books.remove(coin);
+        }
+    }
+}
+
+/// Routing key of a subscription: its coin (and candle interval), its user, or
+/// both for per-user asset data
+fn subscription_key(subscription: &Value) -> Option<String> {
+    match (
+        subscription["coin"].as_str(),
+        subscription["interval"].as_str(),
+        subscription["user"].as_str(),
+    ) {
+        (Some(coin), Some(interval), _) => Some(candle_key(coin, interval)),
+        (Some(coin), None, Some(user)) => Some(user_coin_key(user, coin)),
+        (Some(coin), None, None) => Some(coin.to_string()),
+        (None, _, user) => user.map(normalize_user),
+    }
+}
+
+fn candle_key(coin: &str, interval: &str) -> String {
+    format!("{coin} {interval}")
+}
+
+fn user_coin_key(user: &str, coin: &str) -> String {
+    format!("{} {coin}", normalize_user(user))
+}
+
+/// Routing key of an incoming message, comparable with [`subscription_key`]
+fn message_key(val: &BorrowedValue) -> Option<String> {
+    let data = val.get("data")?;
+    // Trades arrive as an array of trades for one coin
+    let item = data
+        .as_array()
+        .and_then(|items| items.first())
+        .unwrap_or(data);
+    // Candles name their coin `s` and interval `i`
+    if let (Some(coin), Some(interval)) = (
+        item.get("s").and_then(|coin| coin.as_str()),
+        item.get("i").and_then(|interval| interval.as_str()),
+    ) {
+        return Some(candle_key(coin, interval));
+    }
+    let coin = item.get("coin").and_then(|coin| coin.as_str());
+    let user = data.get("user").and_then(|user| user.as_str());
+    match (coin, user) {
+        (Some(coin), Some(user)) => Some(user_coin_key(user, coin)),
+        (Some(coin), None) => Some(coin.to_string()),
+        (None, user) => user.map(normalize_user),
+    }
+}
+
+fn normalize_user(user: &str) -> String {
+    user.trim_start_matches("0x").to_ascii_lowercase()
+}
+
+/// `subscribe` or `unsubscribe` message for a subscription
+fn subscription_message(method: &str, subscription: &Value) -> Bytes {
+    let msg = json!({
+        "method": method,
+        "subscription": subscription
+    });
+    Bytes::from(msg.to_string())
 }
 
 #[derive(Deserialize, Debug)]
@@ -70,6 +389,9 @@ struct BasicOrderBorrowed<'a> {
 
 #[derive(Deserialize, Debug)]
 struct UserFillsDataBorrowed<'a> {
+    /// Set on the first message, which replays recent fills
+    #[serde(rename = "isSnapshot", default)]
+    is_snapshot: bool,
     user: &'a str,
     fills: Vec<UserFillBorrowed<'a>>,
 }
@@ -87,6 +409,216 @@ struct UserFillBorrowed<'a> {
     fee: &'a str,
 }
 
+#[derive(Deserialize, Debug)]
+struct TwapSliceFillsDataBorrowed<'a> {
+    #[serde(rename = "isSnapshot", default)]
+    is_snapshot: bool,
+    user: &'a str,
+    #[serde(rename = "twapSliceFills")]
+    twap_slice_fills: Vec<TwapSliceFillBorrowed<'a>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct TwapSliceFillBorrowed<'a> {
+    #[serde(borrow)]
+    fill: UserFillBorrowed<'a>,
+    #[serde(rename = "twapId")]
+    twap_id: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct AllMidsBorrowed<'a> {
+    #[serde(borrow)]
+    mids: HashMap<&'a str, &'a str>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CandleBorrowed<'a> {
+    /// Open time
+    t: u64,
+    /// Coin
+    s: &'a str,
+    i: CandleInterval,
+    o: &'a str,
+    c: &'a str,
+    h: &'a str,
+    l: &'a str,
+    v: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct NotificationBorrowed {
+    // Free text may contain escapes, so it is not borrowed
+    notification: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct WebData2Borrowed<'a> {
+    user: &'a str,
+    server_time: u64,
+    #[serde(borrow)]
+    clearinghouse_state: ClearinghouseStateBorrowed<'a>,
+    #[serde(borrow, default)]
+    open_orders: Vec<OpenOrderBorrowed<'a>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ClearinghouseStateBorrowed<'a> {
+    #[serde(borrow)]
+    margin_summary: MarginSummaryBorrowed<'a>,
+    withdrawable: &'a str,
+    #[serde(borrow)]
+    asset_positions: Vec<AssetPositionBorrowed<'a>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct MarginSummaryBorrowed<'a> {
+    account_value: &'a str,
+    total_margin_used: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct AssetPositionBorrowed<'a> {
+    #[serde(borrow)]
+    position: PositionBorrowed<'a>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PositionBorrowed<'a> {
+    coin: &'a str,
+    szi: &'a str,
+    entry_px: Option<&'a str>,
+    unrealized_pnl: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct OpenOrderBorrowed<'a> {
+    coin: &'a str,
+    side: &'a str,
+    limit_px: &'a str,
+    sz: &'a str,
+    oid: u64,
+    timestamp: u64,
+    orig_sz: &'a str,
+    #[serde(default)]
+    cloid: Option<&'a str>,
+}
+
+/// Payload of the `user` channel: exactly one of these keys is present
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+enum UserEventBorrowed<'a> {
+    #[serde(borrow)]
+    Fills(Vec<UserFillBorrowed<'a>>),
+    #[serde(borrow)]
+    Funding(FundingBorrowed<'a>),
+    #[serde(borrow)]
+    Liquidation(LiquidationBorrowed<'a>),
+    #[serde(borrow)]
+    NonUserCancel(Vec<NonUserCancelBorrowed<'a>>),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct FundingBorrowed<'a> {
+    time: u64,
+    coin: &'a str,
+    usdc: &'a str,
+    szi: &'a str,
+    funding_rate: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LiquidationBorrowed<'a> {
+    lid: u64,
+    liquidator: &'a str,
+    liquidated_user: &'a str,
+    liquidated_ntl_pos: &'a str,
+    liquidated_account_value: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+struct NonUserCancelBorrowed<'a> {
+    coin: &'a str,
+    oid: u64,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct UserFundingsDataBorrowed<'a> {
+    #[serde(default)]
+    is_snapshot: bool,
+    #[serde(borrow)]
+    fundings: Vec<FundingBorrowed<'a>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LedgerUpdatesDataBorrowed<'a> {
+    #[serde(default)]
+    is_snapshot: bool,
+    #[serde(borrow)]
+    non_funding_ledger_updates: Vec<LedgerUpdateBorrowed<'a>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LedgerUpdateBorrowed<'a> {
+    time: u64,
+    hash: &'a str,
+    delta: Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct ActiveAssetCtxBorrowed<'a> {
+    coin: &'a str,
+    ctx: AssetCtx,
+}
+
+#[derive(Deserialize, Debug)]
+struct ActiveSpotAssetCtxBorrowed<'a> {
+    coin: &'a str,
+    #[serde(borrow)]
+    ctx: SpotAssetCtxBorrowed<'a>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpotAssetCtxBorrowed<'a> {
+    day_ntl_vlm: &'a str,
+    prev_day_px: &'a str,
+    mark_px: Option<&'a str>,
+    mid_px: Option<&'a str>,
+    circulating_supply: &'a str,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct ActiveAssetDataBorrowed<'a> {
+    user: &'a str,
+    coin: &'a str,
+    leverage: Leverage,
+    #[serde(borrow)]
+    max_trade_szs: Vec<&'a str>,
+    #[serde(borrow)]
+    available_to_trade: Vec<&'a str>,
+    #[serde(default)]
+    mark_px: Option<&'a str>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TwapHistoryDataBorrowed {
+    #[serde(default)]
+    is_snapshot: bool,
+    history: Vec<TwapHistoryEntry>,
+}
+
 impl<T: WsTransport + Clone + 'static> HlWs<T> {
     pub fn new(txp: T, testnet: bool) -> Self {
         let url = if testnet {
@@ -97,16 +629,27 @@ impl<T: WsTransport + Clone + 'static> HlWs<T> {
         Self {
             txp,
             url: url.into(),
+            policy: ReconnectPolicy::default(),
+            manager: Mutex::new(None),
+            next_id: AtomicU64::new(0),
+            books: Arc::default(),
         }
     }
 
+    /// Retry behaviour of the shared connection
+    pub fn with_reconnect_policy(mut self, policy: ReconnectPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Subscribe on the shared connection; dropping the handle unsubscribes
     pub async fn subscribe(
         &self,
         kind: StreamKind,
         coin: Option<&str>,
         out: mpsc::UnboundedSender<StreamEvent>,
         address_hex: Option<&str>,
-    ) -> Result<(), DexError> {
+    ) -> Result<SubscriptionHandle, DexError> {
         let subscription = match kind {
             StreamKind::Bbo => json!({
                 "type": "bbo",
@@ -120,6 +663,18 @@ impl<T: WsTransport + Clone + 'static> HlWs<T> {
                 "type": "l2Book",
                 "coin": coin.ok_or(DexError::Other("coin required for l2Book".into()))?
             }),
+            StreamKind::L2BookAggregated(aggregation) => {
+                check_book_aggregation(&aggregation)?;
+                let mut subscription = json!({
+                    "type": "l2Book",
+                    "coin": coin.ok_or(DexError::Other("coin required for l2Book".into()))?,
+                    "nSigFigs": aggregation.n_sig_figs
+                });
+                if let Some(mantissa) = aggregation.mantissa {
+                    subscription["mantissa"] = json!(mantissa);
+                }
+                subscription
+            }
             StreamKind::Orders => json!({
                 "type": "orderUpdates",
                 "user": address_hex.ok_or(DexError::Other("address required for orders".into()))?
@@ -128,108 +683,188 @@ impl<T: WsTransport + Clone + 'static> HlWs<T> {
                 "type": "userFills",
                 "user": address_hex.ok_or(DexError::Other("address required for fills".into()))?
             }),
+            StreamKind::TwapSliceFills => json!({
+                "type": "userTwapSliceFills",
+                "user": address_hex.ok_or(DexError::Other("address required for TWAP slice fills".into()))?
+            }),
+            StreamKind::AllMids => json!({ "type": "allMids" }),
+            StreamKind::Candle(interval) => json!({
+                "type": "candle",
+                "coin": coin.ok_or(DexError::Other("coin required for candle".into()))?,
+                "interval": interval.as_str()
+            }),
+            StreamKind::ActiveAssetCtx => json!({
+                "type": "activeAssetCtx",
+                "coin": coin.ok_or(DexError::Other("coin required for activeAssetCtx".into()))?
+            }),
+            StreamKind::ActiveAssetData => json!({
+                "type": "activeAssetData",
+                "user": address_hex.ok_or(DexError::Other("address required for activeAssetData".into()))?,
+                "coin": coin.ok_or(DexError::Other("coin required for activeAssetData".into()))?
+            }),
+            StreamKind::Notification => json!({
+                "type": "notification",
+                "user": address_hex.ok_or(DexError::Other("address required for notifications".into()))?
+            }),
+            StreamKind::WebData2 => json!({
+                "type": "webData2",
+                "user": address_hex.ok_or(DexError::Other("address required for webData2".into()))?
+            }),
+            StreamKind::UserEvents => json!({
+                "type": "userEvents",
+                "user": address_hex.ok_or(DexError::Other("address required for user events".into()))?
+            }),
+            StreamKind::UserFundings => json!({
+                "type": "userFundings",
+                "user": address_hex.ok_or(DexError::Other("address required for user fundings".into()))?
+            }),
+            StreamKind::UserNonFundingLedgerUpdates => json!({
+                "type": "userNonFundingLedgerUpdates",
+                "user": address_hex.ok_or(DexError::Other("address required for ledger updates".into()))?
+            }),
+            StreamKind::UserTwapHistory => json!({
+                "type": "userTwapHistory",
+                "user": address_hex.ok_or(DexError::Other("address required for TWAP history".into()))?
+            }),
         };
 
-        let msg = json!({
-            "method": "subscribe",
-            "subscription": subscription
-        });
-
-        // Clone necessary data for the reconnection loop
-        let txp = self.txp.clone();
-        let url = self.url.clone();
-        let stream_kind = kind;
-        let msg_bytes = Bytes::from(msg.to_string());
-
-        tokio::spawn(async move {
-            let mut retry_count = 0;
-            const MAX_RETRIES: u32 = 10;
-            const BASE_DELAY_MS: u64 = 1000;
-            const MAX_DELAY_MS: u64 = 30000;
-
-            loop {
-                match Self::connect_and_subscribe(&txp, &url, &msg_bytes, &out, stream_kind).await {
-                    Ok(_) => {
-                        // Connection ended normally, reset retry count
-                        retry_count = 0;
-                    }
-                    Err(_) => {
-                        retry_count += 1;
-                        if retry_count >= MAX_RETRIES {
-                            break;
-                        }
-                    }
-                }
-
-                // Exponential backoff with simple jitter
-                let delay_ms = std::cmp::min(
-                    BASE_DELAY_MS * 2_u64.pow(retry_count.saturating_sub(1)),
-                    MAX_DELAY_MS,
-                );
-                // Simple jitter using retry_count for deterministic but varied delays
-                let jitter = (retry_count as u64 * 137) % (delay_ms / 4 + 1); // Add up to 25% jitter
-                let total_delay = delay_ms + jitter;
+        let book = match kind {
+            StreamKind::L2Book => Some(self.claim_book(coin.unwrap_or_default(), None)?),
+            StreamKind::L2BookAggregated(aggregation) => {
+                Some(self.claim_book(coin.unwrap_or_default(), Some(aggregation))?)
+            }
+            _ => None,
+        };
 
-                sleep(Duration::from_millis(total_delay)).await;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (subscriber, state) = Subscriber::new(id, kind, subscription, out);
+        let commands = self.send_command(Command::Subscribe(subscriber)).commands;
+        let books = self.books.clone();
+        Ok(SubscriptionHandle::new(state, move || {
+            let _ = commands.send(Command::Unsubscribe(id));
+            if let Some(coin) = book {
+                release_book(&books, &coin);
             }
-        });
+        }))
+    }
 
-        Ok(())
+    /// Reserve `coin`'s L2 book for `aggregation`. Book messages do not say which
+    /// aggregation they use, so a coin's book can only be streamed with one at a time.
+    fn claim_book(
+        &self,
+        coin: &str,
+        aggregation: Option<BookAggregation>,
+    ) -> Result<String, DexError> {
+        let mut books = self.books.lock().unwrap_or_else(|e| e.into_inner());
+        let (active, count) = books.entry(coin.to_string()).or_insert((aggregation, 0));
+        if *active != aggregation {
+            return Err(DexError::Validation(format!(
+                "{} L2 book is already subscribed with a different aggregation",
+                coin
+            )));
+        }
+        *count += 1;
+        Ok(coin.to_string())
     }
 
-    async fn connect_and_subscribe<U: WsTransport + 'static>(
-        txp: &U,
-        url: &str,
-        msg_bytes: &Bytes,
-        out: &mpsc::UnboundedSender<StreamEvent>,
-        stream_kind: StreamKind,
-    ) -> Result<(), DexError> {
-        let mut ws = txp.connect(url).await?;
-        ws.send_message(msg_bytes.clone()).await?;
+    /// Send a signed exchange action as a `post` on the shared connection and return
+    /// the `response` body, like [`HlRest::exchange`](crate::http::HlRest::exchange)
+    pub async fn post_action(&self, payload: Value) -> Result<Value, DexError> {
+        self.post("action", payload).await
+    }
 
-        loop {
-            match ws.read_message().await {
-                Ok(bytes) => {
-                    if Self::handle_message(&bytes, out, stream_kind)
-                        .await
-                        .is_err()
-                    {
-                        // Ignore parse errors and continue
-                    }
-                }
-                Err(e) => {
-                    return Err(e);
-                }
-            }
-        }
+    /// Send an info request as a `post` on the shared connection and return its data
+    pub async fn post_info(&self, payload: Value) -> Result<Value, DexError> {
+        self.post("info", payload).await
     }
 
-    async fn handle_message(
-        bytes: &[u8],
-        out: &mpsc::UnboundedSender<StreamEvent>,
-        kind: StreamKind,
-    ) -> Result<(), DexError> {
-        let mut bytes_mut = bytes.to_vec();
-        let val: BorrowedValue = simd_json::to_borrowed_value(&mut bytes_mut)
-            .map_err(|e| DexError::Parse(format!("SIMD JSON parse error: {}", e)))?;
+    async fn post(&self, kind: &str, payload: Value) -> Result<Value, DexError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let message = json!({
+            "method": "post",
+            "id": id,
+            "request": {"type": kind, "payload": payload}
+        });
+        let (reply, response) = oneshot::channel();
+        self.send_command(Command::Post(Post {
+            id,
+            message: Bytes::from(message.to_string()),
+            reply,
+        }));
 
-        if val.get("method").map(|v| v.as_str()) == Some(Some("subscriptionResponse")) {
-            return Ok(());
+        match timeout(POST_TIMEOUT, response).await {
+            Ok(Ok(result)) => result,
+            // The connection dropped or gave up; the request may or may not have been applied
+            Ok(Err(_)) => Err(DexError::Ws(
+                "Connection lost awaiting post response".into(),
+            )),
+            Err(_) => Err(DexError::Timeout),
         }
+    }
 
-        let event = match kind {
-            StreamKind::Bbo => Self::parse_bbo_simd(&val)?,
-            StreamKind::Trades => Self::parse_trades_simd(&val)?,
-            StreamKind::L2Book => Self::parse_l2_book_simd(&val)?,
-            StreamKind::Orders => Self::parse_orders_simd(&val)?,
-            StreamKind::Fills => Self::parse_fills_simd(&val)?,
+    /// Hand a command to the connection manager, starting one if none is running
+    fn send_command(&self, command: Command) -> Manager {
+        let mut manager = self.manager.lock().unwrap_or_else(|e| e.into_inner());
+        let command = match manager.as_ref() {
+            Some(running) => match running.commands.send(command) {
+                Ok(()) => return running.clone(),
+                Err(mpsc::error::SendError(command)) => command,
+            },
+            None => command,
         };
 
-        if let Some(ev) = event {
-            let _ = out.send(ev);
-        }
+        // First subscription or post, or the previous manager gave up reconnecting
+        let (commands, rx) = mpsc::unbounded_channel();
+        let _ = commands.send(command);
+        let connection = Connection::<T> {
+            txp: self.txp.clone(),
+            url: self.url.clone(),
+            policy: self.policy,
+            commands: rx,
+            subscribers: Vec::new(),
+            queued: Vec::new(),
+            posts: HashMap::new(),
+            posting: false,
+            snapshots: HashMap::new(),
+            ping_sent: None,
+            last_data: Instant::now(),
+            received_data: false,
+        };
+        tokio::spawn(connection.run());
+        manager.insert(Manager { commands }).clone()
+    }
 
-        Ok(())
+    /// Parse a message into events, one per element of batched channels. `user` is
+    /// the subscription's user, for channels whose messages do not name it.
+    fn parse_message(
+        kind: StreamKind,
+        val: &BorrowedValue,
+        user: Option<&str>,
+    ) -> Result<Vec<StreamEvent>, DexError> {
+        match kind {
+            StreamKind::Bbo => Self::parse_bbo_simd(val).map(Vec::from_iter),
+            StreamKind::Trades => Self::parse_trades_simd(val),
+            StreamKind::L2Book | StreamKind::L2BookAggregated(_) => {
+                Self::parse_l2_book_simd(val).map(Vec::from_iter)
+            }
+            StreamKind::Orders => Self::parse_orders_simd(val),
+            StreamKind::Fills => Self::parse_fills_simd(val),
+            StreamKind::TwapSliceFills => Self::parse_twap_slice_fills_simd(val),
+            StreamKind::AllMids => Self::parse_all_mids_simd(val).map(Vec::from_iter),
+            StreamKind::Candle(_) => Self::parse_candle_simd(val).map(Vec::from_iter),
+            StreamKind::Notification => Self::parse_notification_simd(val).map(Vec::from_iter),
+            StreamKind::WebData2 => Self::parse_web_data2_simd(val).map(Vec::from_iter),
+            StreamKind::UserEvents => Self::parse_user_events_simd(val, user.unwrap_or_default()),
+            StreamKind::UserFundings => Self::parse_user_fundings_simd(val),
+            StreamKind::UserNonFundingLedgerUpdates => Self::parse_ledger_updates_simd(val),
+            StreamKind::ActiveAssetCtx => {
+                Self::parse_active_asset_ctx_simd(val).map(Vec::from_iter)
+            }
+            StreamKind::ActiveAssetData => {
+                Self::parse_active_asset_data_simd(val).map(Vec::from_iter)
+            }
+            StreamKind::UserTwapHistory => Self::parse_twap_history_simd(val),
+        }
     }
 
     fn parse_bbo_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
@@ -256,38 +891,42 @@ impl<T: WsTransport + Clone + 'static> HlWs<T> {
         }
     }
 
-    fn parse_trades_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
-        if let Some(data) = val.get("data") {
-            if let Ok(trades) =
-                simd_json::serde::from_borrowed_value::<Vec<TradeDataBorrowed>>(data.clone())
-            {
-                if let Some(trade_data) = trades.into_iter().next() {
-                    let trade = Trade {
-                        id: trade_data.hash.to_string(),
-                        ts: trade_data.time,
-                        side: if trade_data.side == "B" {
-                            Side::Buy
-                        } else {
-                            Side::Sell
-                        },
-                        price: price(
-                            trade_data
-                                .px
-                                .parse()
-                                .map_err(|_| DexError::Parse("Invalid trade price".into()))?,
-                        ),
-                        qty: qty(trade_data
-                            .sz
+    fn parse_trades_simd(val: &BorrowedValue) -> Result<Vec<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(Vec::new());
+        };
+        let Ok(trades) =
+            simd_json::serde::from_borrowed_value::<Vec<TradeDataBorrowed>>(data.clone())
+        else {
+            return Ok(Vec::new());
+        };
+        trades
+            .into_iter()
+            .map(|trade_data| {
+                let trade = Trade {
+                    id: trade_data.hash.to_string(),
+                    ts: trade_data.time,
+                    side: if trade_data.side == "B" {
+                        Side::Buy
+                    } else {
+                        Side::Sell
+                    },
+                    price: price(
+                        trade_data
+                            .px
                             .parse()
-                            .map_err(|_| DexError::Parse("Invalid trade size".into()))?),
-                        coin: trade_data.coin.to_string(),
-                        tid: trade_data.tid,
-                    };
-                    return Ok(Some(StreamEvent::Trade(trade)));
-                }
-            }
-        }
-        Ok(None)
+                            .map_err(|_| DexError::Parse("Invalid trade price".into()))?,
+                    ),
+                    qty: qty(trade_data
+                        .sz
+                        .parse()
+                        .map_err(|_| DexError::Parse("Invalid trade size".into()))?),
+                    coin: trade_data.coin.to_string(),
+                    tid: trade_data.tid,
+                };
+                Ok(StreamEvent::Trade(trade))
+            })
+            .collect()
     }
 
     fn parse_l2_book_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
@@ -349,73 +988,800 @@ impl<T: WsTransport + Clone + 'static> HlWs<T> {
         }
     }
 
-    fn parse_orders_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
-        if let Some(data) = val.get("data") {
-            if let Ok(order_updates) =
-                simd_json::serde::from_borrowed_value::<Vec<OrderUpdateBorrowed>>(data.clone())
-            {
-                if let Some(update) = order_updates.into_iter().next() {
-                    let order_event = OrderEvent {
-                        coin: update.order.coin.to_string(),
-                        side: update.order.side.to_string(),
-                        limit_px: update.order.limit_px.to_string(),
-                        sz: update.order.sz.to_string(),
-                        oid: update.order.oid,
-                        status: update.status.to_string(),
-                        timestamp: update.status_timestamp,
-                        order_timestamp: update.order.timestamp,
-                    };
-                    return Ok(Some(StreamEvent::Order(order_event)));
-                }
-            }
-        }
-        Ok(None)
+    fn parse_orders_simd(val: &BorrowedValue) -> Result<Vec<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(Vec::new());
+        };
+        let Ok(order_updates) =
+            simd_json::serde::from_borrowed_value::<Vec<OrderUpdateBorrowed>>(data.clone())
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(order_updates
+            .into_iter()
+            .map(|update| {
+                StreamEvent::Order(OrderEvent {
+                    coin: update.order.coin.to_string(),
+                    side: update.order.side.to_string(),
+                    limit_px: update.order.limit_px.to_string(),
+                    sz: update.order.sz.to_string(),
+                    oid: update.order.oid,
+                    status: update.status.to_string(),
+                    timestamp: update.status_timestamp,
+                    order_timestamp: update.order.timestamp,
+                })
+            })
+            .collect())
     }
 
-    fn parse_fills_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
-        if let Some(data) = val.get("data") {
-            if let Ok(fills_data) =
-                simd_json::serde::from_borrowed_value::<UserFillsDataBorrowed>(data.clone())
-            {
-                if let Some(fill) = fills_data.fills.into_iter().next() {
-                    let fill_event = FillEvent {
-                        coin: fill.coin.to_string(),
-                        side: fill.side.to_string(),
-                        px: fill.px.to_string(),
-                        sz: fill.sz.to_string(),
-                        oid: fill.oid,
-                        tid: fill.tid,
-                        time: fill.time,
-                        fee: fill.fee.to_string(),
-                        hash: fill.hash.to_string(),
-                        user: fills_data.user.to_string(),
-                    };
-                    return Ok(Some(StreamEvent::Fill(fill_event)));
-                }
-            }
-        }
-        Ok(None)
+    fn parse_fills_simd(val: &BorrowedValue) -> Result<Vec<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(Vec::new());
+        };
+        let Ok(fills_data) =
+            simd_json::serde::from_borrowed_value::<UserFillsDataBorrowed>(data.clone())
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(fills_data
+            .fills
+            .iter()
+            .map(|fill| {
+                StreamEvent::Fill(fill_event(fill, fills_data.user, fills_data.is_snapshot))
+            })
+            .collect())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use async_trait::async_trait;
-    use serde_json::json;
+    fn parse_twap_slice_fills_simd(val: &BorrowedValue) -> Result<Vec<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(Vec::new());
+        };
+        let Ok(fills_data) =
+            simd_json::serde::from_borrowed_value::<TwapSliceFillsDataBorrowed>(data.clone())
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(fills_data
+            .twap_slice_fills
+            .iter()
+            .map(|slice| StreamEvent::TwapSliceFill {
+                twap_id: slice.twap_id,
+                fill: fill_event(&slice.fill, fills_data.user, fills_data.is_snapshot),
+            })
+            .collect())
+    }
 
-    #[test]
-    fn test_subscription_message_construction() {
-        // Test BBO subscription
-        let bbo_sub = json!({
-            "type": "bbo",
-            "coin": "BTC"
-        });
-        assert_eq!(bbo_sub["type"], "bbo");
-        assert_eq!(bbo_sub["coin"], "BTC");
+    fn parse_all_mids_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(None);
+        };
+        let Ok(all_mids) = simd_json::serde::from_borrowed_value::<AllMidsBorrowed>(data.clone())
+        else {
+            return Ok(None);
+        };
+        let mids = all_mids
+            .mids
+            .into_iter()
+            .map(|(coin, mid)| (coin.to_string(), mid.to_string()))
+            .collect();
+        Ok(Some(StreamEvent::AllMids(AllMids { mids })))
+    }
 
-        // Test trades subscription
-        let trades_sub = json!({
+    fn parse_candle_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(None);
+        };
+        let Ok(candle) = simd_json::serde::from_borrowed_value::<CandleBorrowed>(data.clone())
+        else {
+            return Ok(None);
+        };
+        Ok(Some(StreamEvent::Candle {
+            coin: candle.s.to_string(),
+            interval: candle.i,
+            candle: Candle {
+                time: candle.t,
+                open: candle.o.to_string(),
+                high: candle.h.to_string(),
+                low: candle.l.to_string(),
+                close: candle.c.to_string(),
+                volume: candle.v.to_string(),
+            },
+        }))
+    }
+
+    fn parse_notification_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(None);
+        };
+        let Ok(notification) =
+            simd_json::serde::from_borrowed_value::<NotificationBorrowed>(data.clone())
+        else {
+            return Ok(None);
+        };
+        Ok(Some(StreamEvent::Notification {
+            message: notification.notification,
+        }))
+    }
+
+    fn parse_web_data2_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(None);
+        };
+        let Ok(web_data) = simd_json::serde::from_borrowed_value::<WebData2Borrowed>(data.clone())
+        else {
+            return Ok(None);
+        };
+        let state = web_data.clearinghouse_state;
+        let positions = state
+            .asset_positions
+            .iter()
+            .map(|asset| {
+                let pos = &asset.position;
+                Ok(Position {
+                    coin: pos.coin.to_string(),
+                    size: pos
+                        .szi
+                        .parse()
+                        .map_err(|_| DexError::Parse("Invalid position size".into()))?,
+                    entry_px: pos
+                        .entry_px
+                        .map(|px| px.parse())
+                        .transpose()
+                        .map_err(|_| DexError::Parse("Invalid entry price".into()))?,
+                    unrealized_pnl: pos
+                        .unrealized_pnl
+                        .parse()
+                        .map_err(|_| DexError::Parse("Invalid unrealized PnL".into()))?,
+                })
+            })
+            .collect::<Result<_, DexError>>()?;
+        let open_orders = web_data
+            .open_orders
+            .iter()
+            .map(|order| OpenOrder {
+                coin: order.coin.to_string(),
+                side: order.side.to_string(),
+                limit_px: order.limit_px.to_string(),
+                sz: order.sz.to_string(),
+                oid: order.oid,
+                timestamp: order.timestamp,
+                orig_sz: order.orig_sz.to_string(),
+                cloid: order.cloid.map(str::to_string),
+            })
+            .collect();
+        Ok(Some(StreamEvent::WebData2(Box::new(WebData2Event {
+            user: web_data.user.to_string(),
+            server_time: web_data.server_time,
+            account_value: state.margin_summary.account_value.to_string(),
+            total_margin_used: state.margin_summary.total_margin_used.to_string(),
+            withdrawable: state.withdrawable.to_string(),
+            positions,
+            open_orders,
+        }))))
+    }
+
+    fn parse_user_events_simd(
+        val: &BorrowedValue,
+        user: &str,
+    ) -> Result<Vec<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(Vec::new());
+        };
+        let Ok(event) = simd_json::serde::from_borrowed_value::<UserEventBorrowed>(data.clone())
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(match event {
+            // Fills on this channel do not name the user
+            UserEventBorrowed::Fills(fills) => {
+                let user = format!("0x{}", normalize_user(user));
+                fills
+                    .iter()
+                    .map(|fill| StreamEvent::Fill(fill_event(fill, &user, false)))
+                    .collect()
+            }
+            UserEventBorrowed::Funding(funding) => {
+                vec![StreamEvent::Funding(funding_event(&funding, false))]
+            }
+            UserEventBorrowed::Liquidation(liquidation) => {
+                vec![StreamEvent::Liquidation(LiquidationEvent {
+                    lid: liquidation.lid,
+                    liquidator: liquidation.liquidator.to_string(),
+                    liquidated_user: liquidation.liquidated_user.to_string(),
+                    liquidated_ntl_pos: liquidation.liquidated_ntl_pos.to_string(),
+                    liquidated_account_value: liquidation.liquidated_account_value.to_string(),
+                })]
+            }
+            UserEventBorrowed::NonUserCancel(cancels) => cancels
+                .iter()
+                .map(|cancel| StreamEvent::NonUserCancel {
+                    coin: cancel.coin.to_string(),
+                    oid: cancel.oid,
+                })
+                .collect(),
+        })
+    }
+
+    fn parse_user_fundings_simd(val: &BorrowedValue) -> Result<Vec<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(Vec::new());
+        };
+        let Ok(fundings) =
+            simd_json::serde::from_borrowed_value::<UserFundingsDataBorrowed>(data.clone())
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(fundings
+            .fundings
+            .iter()
+            .map(|funding| StreamEvent::Funding(funding_event(funding, fundings.is_snapshot)))
+            .collect())
+    }
+
+    fn parse_ledger_updates_simd(val: &BorrowedValue) -> Result<Vec<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(Vec::new());
+        };
+        let Ok(updates) =
+            simd_json::serde::from_borrowed_value::<LedgerUpdatesDataBorrowed>(data.clone())
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(updates
+            .non_funding_ledger_updates
+            .into_iter()
+            .map(|update| {
+                StreamEvent::LedgerUpdate(LedgerUpdateEvent {
+                    time: update.time,
+                    hash: update.hash.to_string(),
+                    kind: update.delta["type"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    usdc: update.delta["usdc"].as_str().map(str::to_string),
+                    delta: update.delta,
+                    is_snapshot: updates.is_snapshot,
+                })
+            })
+            .collect())
+    }
+
+    fn parse_active_asset_ctx_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(None);
+        };
+        if val.get("channel").and_then(|channel| channel.as_str()) == Some("activeSpotAssetCtx") {
+            let Ok(spot) =
+                simd_json::serde::from_borrowed_value::<ActiveSpotAssetCtxBorrowed>(data.clone())
+            else {
+                return Ok(None);
+            };
+            return Ok(Some(StreamEvent::ActiveSpotAssetCtx {
+                coin: spot.coin.to_string(),
+                ctx: SpotAssetCtx {
+                    day_ntl_vlm: spot.ctx.day_ntl_vlm.to_string(),
+                    prev_day_px: spot.ctx.prev_day_px.to_string(),
+                    mark_px: spot.ctx.mark_px.map(str::to_string),
+                    mid_px: spot.ctx.mid_px.map(str::to_string),
+                    circulating_supply: spot.ctx.circulating_supply.to_string(),
+                },
+            }));
+        }
+        let Ok(perp) =
+            simd_json::serde::from_borrowed_value::<ActiveAssetCtxBorrowed>(data.clone())
+        else {
+            return Ok(None);
+        };
+        Ok(Some(StreamEvent::ActiveAssetCtx {
+            coin: perp.coin.to_string(),
+            ctx: Box::new(perp.ctx),
+        }))
+    }
+
+    fn parse_active_asset_data_simd(val: &BorrowedValue) -> Result<Option<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(None);
+        };
+        let Ok(asset_data) =
+            simd_json::serde::from_borrowed_value::<ActiveAssetDataBorrowed>(data.clone())
+        else {
+            return Ok(None);
+        };
+        Ok(Some(StreamEvent::ActiveAssetData(ActiveAssetDataEvent {
+            user: asset_data.user.to_string(),
+            coin: asset_data.coin.to_string(),
+            leverage: asset_data.leverage,
+            max_trade_szs: asset_data
+                .max_trade_szs
+                .iter()
+                .map(|sz| sz.to_string())
+                .collect(),
+            available_to_trade: asset_data
+                .available_to_trade
+                .iter()
+                .map(|sz| sz.to_string())
+                .collect(),
+            mark_px: asset_data.mark_px.map(str::to_string),
+        })))
+    }
+
+    fn parse_twap_history_simd(val: &BorrowedValue) -> Result<Vec<StreamEvent>, DexError> {
+        let Some(data) = val.get("data") else {
+            return Ok(Vec::new());
+        };
+        let Ok(history) =
+            simd_json::serde::from_borrowed_value::<TwapHistoryDataBorrowed>(data.clone())
+        else {
+            return Ok(Vec::new());
+        };
+        Ok(history
+            .history
+            .into_iter()
+            .map(|entry| StreamEvent::TwapHistory {
+                entry,
+                is_snapshot: history.is_snapshot,
+            })
+            .collect())
+    }
+}
+
+/// Connection manager: owns the shared socket, replays subscriptions on every
+/// (re)connect and routes incoming messages to subscribers by channel and key.
+/// Subscribers also receive the connection's lifecycle events. The socket is
+/// closed while no subscriptions are active.
+struct Connection<T: WsTransport + Clone + 'static> {
+    txp: T,
+    url: String,
+    policy: ReconnectPolicy,
+    commands: mpsc::UnboundedReceiver<Command>,
+    subscribers: Vec<Subscriber>,
+    /// Posts waiting for a connection
+    queued: Vec<Post>,
+    /// Replies for sent posts, by request id
+    posts: HashMap<u64, oneshot::Sender<Result<Value, DexError>>>,
+    /// Whether the client posts requests, which keeps the socket open without subscriptions
+    posting: bool,
+    /// Events replayed to late joiners of an active subscription, by topic
+    snapshots: HashMap<String, Vec<StreamEvent>>,
+    /// When the last unanswered app-level ping was sent
+    ping_sent: Option<Instant>,
+    /// When subscription data was last delivered; pongs and post responses don't count
+    last_data: Instant,
+    /// Whether the current connection has delivered subscription data
+    received_data: bool,
+}
+
+impl<T: WsTransport + Clone + 'static> Connection<T> {
+    async fn run(mut self) {
+        let mut attempt = 0;
+
+        loop {
+            self.subscribers.retain(|sub| !sub.out.is_closed());
+            if !self.active() {
+                // Idle until the next subscription or post arrives
+                match self.commands.recv().await {
+                    Some(command) => {
+                        self.apply(command);
+                        continue;
+                    }
+                    None => break,
+                }
+            }
+
+            if let Ok(conn) = self.txp.connect(&self.url).await {
+                let connected_at = Instant::now();
+                let result = self.serve(conn).await;
+                // Unanswered posts fail with the connection
+                self.posts.clear();
+                // A socket that is dropped right away does not count as a recovery
+                if self.received_data || connected_at.elapsed() >= STABLE_CONNECTION {
+                    attempt = 0;
+                }
+                match result {
+                    Ok(()) => {
+                        attempt = 0;
+                        self.set_state(ConnectionState::Connecting);
+                        continue;
+                    }
+                    Err(e) => self.broadcast(StreamEvent::Disconnected {
+                        reason: e.to_string(),
+                    }),
+                }
+            }
+
+            attempt += 1;
+            if self.policy.exhausted(attempt) {
+                self.broadcast(StreamEvent::GaveUp);
+                break;
+            }
+            self.set_state(ConnectionState::Reconnecting);
+            sleep(self.policy.delay(attempt)).await;
+        }
+
+        self.set_state(ConnectionState::Closed);
+    }
+
+    /// Whether the socket is needed: live subscriptions, or a client that posts
+    fn active(&self) -> bool {
+        !self.subscribers.is_empty() || self.posting
+    }
+
+    /// Report a connection state to every subscriber's handle
+    fn set_state(&self, state: ConnectionState) {
+        for sub in &self.subscribers {
+            sub.state.send_replace(state);
+        }
+    }
+
+    /// Send an event to every subscriber
+    fn broadcast(&self, event: StreamEvent) {
+        for sub in &self.subscribers {
+            let _ = sub.out.send(event.clone());
+        }
+    }
+
+    /// Apply a command while disconnected
+    fn apply(&mut self, command: Command) {
+        match command {
+            Command::Subscribe(sub) => self.subscribers.push(sub),
+            Command::Unsubscribe(id) => self.subscribers.retain(|sub| sub.id != id),
+            Command::Post(post) => {
+                self.posting = true;
+                self.queued.push(post);
+            }
+        }
+    }
+
+    /// Track a post's reply and return its message
+    fn send_post(&mut self, post: Post) -> Bytes {
+        // Forget posts whose caller timed out
+        self.posts.retain(|_, reply| !reply.is_closed());
+        self.posts.insert(post.id, post.reply);
+        post.message
+    }
+
+    /// Serve one connection until it fails or goes stale (`Err`), or it is no longer
+    /// needed (`Ok`)
+    async fn serve(
+        &mut self,
+        conn: Box<dyn WsConnection + Send + Sync + Unpin>,
+    ) -> Result<(), DexError> {
+        let (mut reader, mut writer) = conn.split();
+        // Subscriptions go live again as the server acknowledges them
+        for sub in &mut self.subscribers {
+            sub.live = false;
+        }
+        self.snapshots.clear();
+
+        // Replay every active subscription once
+        let mut replayed: Vec<&Value> = Vec::new();
+        for sub in &self.subscribers {
+            if !replayed.contains(&&sub.subscription) {
+                writer
+                    .send_message(subscription_message("subscribe", &sub.subscription))
+                    .await?;
+                replayed.push(&sub.subscription);
+            }
+        }
+        for post in std::mem::take(&mut self.queued) {
+            let msg = self.send_post(post);
+            writer.send_message(msg).await?;
+        }
+
+        // Reads are not cancel-safe, so they run in their own task
+        let (msg_tx, mut messages) = mpsc::unbounded_channel();
+        let read_task = tokio::spawn(async move {
+            loop {
+                let msg = reader.read_message().await;
+                let failed = msg.is_err();
+                if msg_tx.send(msg).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        let mut heartbeat = interval_at(Instant::now() + PING_INTERVAL, PING_INTERVAL);
+        heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        self.last_data = Instant::now();
+        self.received_data = false;
+        self.ping_sent = None;
+
+        let result = 'serve: loop {
+            let outgoing = tokio::select! {
+                command = self.commands.recv() => match command {
+                    Some(Command::Subscribe(mut sub)) => {
+                        if is_continuous(sub.kind) && !self.expects_data() {
+                            // Silence is measured from the first continuous stream
+                            self.last_data = Instant::now();
+                        }
+                        let existing = self.subscribers.iter().find(|other| other.topic == sub.topic);
+                        let outgoing = match existing {
+                            // The server sends no new snapshot, so replay the cached one
+                            Some(other) if other.live => {
+                                sub.go_live();
+                                for event in self.snapshots.get(&sub.topic).into_iter().flatten() {
+                                    let _ = sub.out.send(event.clone());
+                                }
+                                Vec::new()
+                            }
+                            // Goes live with the pending acknowledgement
+                            Some(_) => Vec::new(),
+                            None => vec![subscription_message("subscribe", &sub.subscription)],
+                        };
+                        self.subscribers.push(sub);
+                        outgoing
+                    }
+                    Some(Command::Unsubscribe(id)) => {
+                        let (removed, kept) = std::mem::take(&mut self.subscribers)
+                            .into_iter()
+                            .partition(|sub| sub.id == id);
+                        self.subscribers = kept;
+                        self.orphaned(removed)
+                    }
+                    Some(Command::Post(post)) => {
+                        self.posting = true;
+                        vec![self.send_post(post)]
+                    }
+                    // Every handle and the client are gone
+                    None => {
+                        self.subscribers.clear();
+                        self.posting = false;
+                        Vec::new()
+                    }
+                },
+                msg = messages.recv() => match msg {
+                    Some(Ok(bytes)) => self.dispatch(bytes),
+                    Some(Err(e)) => break Err(e),
+                    None => break Err(DexError::Ws("Reader stopped".into())),
+                },
+                now = heartbeat.tick() => {
+                    if self.ping_sent.is_some_and(|sent| now - sent >= PONG_TIMEOUT) {
+                        break Err(DexError::Ws("Pong not received".into()));
+                    }
+                    if self.expects_data() && now - self.last_data >= STALE_TIMEOUT {
+                        break Err(DexError::Ws("No data received".into()));
+                    }
+                    self.ping_sent.get_or_insert(now);
+                    vec![Bytes::from(json!({"method": "ping"}).to_string())]
+                }
+            };
+
+            for msg in outgoing {
+                if let Err(e) = writer.send_message(msg).await {
+                    break 'serve Err(e);
+                }
+            }
+            if !self.active() {
+                let _ = writer.close().await;
+                break Ok(());
+            }
+        };
+
+        read_task.abort();
+        result
+    }
+
+    /// Route a message to the subscribers of its channel and key. Subscribers whose
+    /// receiver is gone are dropped; returns the resulting unsubscribe messages.
+    fn dispatch(&mut self, mut bytes: Vec<u8>) -> Vec<Bytes> {
+        let Ok(val) = simd_json::to_borrowed_value(&mut bytes) else {
+            return Vec::new();
+        };
+        // Subscription acks and other control messages match no subscriber
+        let Some(channel) = val.get("channel").and_then(|channel| channel.as_str()) else {
+            return Vec::new();
+        };
+        if channel == "pong" {
+            self.ping_sent = None;
+            return Vec::new();
+        }
+        if channel == "post" {
+            self.answer_post(&val);
+            return Vec::new();
+        }
+        if channel == "subscriptionResponse" {
+            self.acknowledge(&val);
+            return Vec::new();
+        }
+        let key = message_key(&val);
+        // Order updates, user events and notifications name no user, so they can only
+        // be attributed while every subscriber of the channel shares one key
+        if key.is_none() && self.channel_keys(channel) > 1 {
+            return Vec::new();
+        }
+
+        let mut delivered = false;
+        let mut replays: Vec<(String, Replay, Vec<StreamEvent>)> = Vec::new();
+        let (kept, removed) = std::mem::take(&mut self.subscribers)
+            .into_iter()
+            .partition(|sub| {
+                if !sub.matches(channel, key.as_deref()) {
+                    return true;
+                }
+                delivered = true;
+                match HlWs::<T>::parse_message(sub.kind, &val, sub.key.as_deref()) {
+                    Ok(events) if !events.is_empty() => {
+                        let replay = replay(sub.kind);
+                        if replay != Replay::Nothing
+                            && !replays.iter().any(|(topic, ..)| *topic == sub.topic)
+                        {
+                            replays.push((sub.topic.clone(), replay, events.clone()));
+                        }
+                        events.into_iter().all(|event| sub.out.send(event).is_ok())
+                    }
+                    // Ignore parse errors and continue
+                    _ => !sub.out.is_closed(),
+                }
+            });
+        self.subscribers = kept;
+        if delivered {
+            self.last_data = Instant::now();
+            self.received_data = true;
+        }
+
+        let is_snapshot = val
+            .get("data")
+            .and_then(|data| data.get("isSnapshot"))
+            .and_then(|flag| flag.as_bool())
+            .unwrap_or(false);
+        for (topic, replay, events) in replays {
+            let cached = self.snapshots.entry(topic).or_default();
+            if replay == Replay::Latest || is_snapshot {
+                cached.clear();
+            }
+            cached.extend(events);
+            if cached.len() > REPLAY_HISTORY_LIMIT {
+                cached.drain(..cached.len() - REPLAY_HISTORY_LIMIT);
+            }
+        }
+        self.orphaned(removed)
+    }
+
+    /// Mark the subscriptions a `subscriptionResponse` acknowledges live
+    fn acknowledge(&mut self, val: &BorrowedValue) {
+        let Some(data) = val.get("data") else {
+            return;
+        };
+        if data.get("method").and_then(|method| method.as_str()) != Some("subscribe") {
+            return;
+        }
+        let Some(Ok(subscription)) = data.get("subscription").map(serde_json::to_value) else {
+            return;
+        };
+        // The echo may add defaults, so match on type and routing key
+        let key = subscription_key(&subscription);
+        for sub in &mut self.subscribers {
+            if !sub.live && sub.subscription["type"] == subscription["type"] && sub.key == key {
+                sub.go_live();
+            }
+        }
+    }
+
+    /// Whether a subscribed stream ticks continuously, so silence means a dead
+    /// connection. Quiet streams rely on the pong timeout alone.
+    fn expects_data(&self) -> bool {
+        self.subscribers.iter().any(|sub| is_continuous(sub.kind))
+    }
+
+    /// Number of distinct subscription keys among the subscribers of `channel`
+    fn channel_keys(&self, channel: &str) -> usize {
+        let mut keys: Vec<Option<&str>> = Vec::new();
+        for sub in &self.subscribers {
+            let key = sub.key.as_deref();
+            if sub.matches(channel, None) && !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys.len()
+    }
+
+    /// Hand a `post` response to the request with the same id
+    fn answer_post(&mut self, val: &BorrowedValue) {
+        let Some(data) = val.get("data") else {
+            return;
+        };
+        let Some(reply) = data
+            .get("id")
+            .and_then(|id| id.as_u64())
+            .and_then(|id| self.posts.remove(&id))
+        else {
+            return;
+        };
+        let result = match data.get("response").map(serde_json::to_value) {
+            Some(Ok(response)) => post_response(response),
+            _ => Err(DexError::Parse(format!("Invalid post response: {}", val))),
+        };
+        let _ = reply.send(result);
+    }
+
+    /// Unsubscribe messages for removed subscribers' subscriptions that no one else uses
+    fn orphaned(&mut self, removed: Vec<Subscriber>) -> Vec<Bytes> {
+        let mut released: Vec<&Value> = Vec::new();
+        for sub in &removed {
+            let shared = self
+                .subscribers
+                .iter()
+                .any(|other| other.topic == sub.topic);
+            if !shared && !released.contains(&&sub.subscription) {
+                self.snapshots.remove(&sub.topic);
+                released.push(&sub.subscription);
+            }
+        }
+        released
+            .into_iter()
+            .map(|subscription| subscription_message("unsubscribe", subscription))
+            .collect()
+    }
+}
+
+/// Unwrap a `post` response: `{"type": "action", "payload": {"status": ..}}`,
+/// `{"type": "info", "payload": {"type": .., "data": ..}}` or `{"type": "error", "payload": "<message>"}`
+fn post_response(mut response: Value) -> Result<Value, DexError> {
+    let mut payload = response["payload"].take();
+    match response["type"].as_str() {
+        Some("action") => exchange_response(payload),
+        Some("info") => Ok(payload["data"].take()),
+        Some("error") => Err(DexError::Exchange {
+            code: None,
+            msg: match payload.as_str() {
+                Some(msg) => msg.to_string(),
+                None => payload.to_string(),
+            },
+        }),
+        _ => Err(DexError::Parse(format!(
+            "Unknown post response type: {}",
+            response["type"]
+        ))),
+    }
+}
+
+fn fill_event(fill: &UserFillBorrowed, user: &str, is_snapshot: bool) -> FillEvent {
+    FillEvent {
+        coin: fill.coin.to_string(),
+        side: fill.side.to_string(),
+        px: fill.px.to_string(),
+        sz: fill.sz.to_string(),
+        oid: fill.oid,
+        tid: fill.tid,
+        time: fill.time,
+        fee: fill.fee.to_string(),
+        hash: fill.hash.to_string(),
+        user: user.to_string(),
+        is_snapshot,
+    }
+}
+
+fn funding_event(funding: &FundingBorrowed, is_snapshot: bool) -> FundingEvent {
+    FundingEvent {
+        coin: funding.coin.to_string(),
+        usdc: funding.usdc.to_string(),
+        szi: funding.szi.to_string(),
+        funding_rate: funding.funding_rate.to_string(),
+        time: funding.time,
+        is_snapshot,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use dex_rs_core::ws::{WsReader, WsWriter};
+    use dex_rs_types::{BookAggregation, LeverageType};
+    use serde_json::json;
+
+    #[test]
+    fn test_subscription_message_construction() {
+        // Test BBO subscription
+        let bbo_sub = json!({
+            "type": "bbo",
+            "coin": "BTC"
+        });
+        assert_eq!(bbo_sub["type"], "bbo");
+        assert_eq!(bbo_sub["coin"], "BTC");
+
+        // Test trades subscription
+        let trades_sub = json!({
             "type": "trades",
             "coin": "ETH"
         });
@@ -481,6 +1847,14 @@ mod tests {
                 "time": 1234567890,
                 "hash": "abcdef123456",
                 "tid": 12345
+            }, {
+                "coin": "BTC",
+                "side": "A",
+                "px": "49999.0",
+                "sz": "0.5",
+                "time": 1234567891,
+                "hash": "abcdef123457",
+                "tid": 12346
             }]
         }"#;
         let mut bytes = mock_message_str.as_bytes().to_vec();
@@ -488,7 +1862,8 @@ mod tests {
 
         let result = HlWs::<DummyTransport>::parse_trades_simd(&mock_message).unwrap();
 
-        if let Some(StreamEvent::Trade(trade)) = result {
+        assert_eq!(result.len(), 2);
+        if let StreamEvent::Trade(trade) = &result[0] {
             assert_eq!(trade.id, "abcdef123456");
             assert_eq!(trade.ts, 1234567890);
             assert_eq!(trade.side, Side::Buy);
@@ -497,6 +1872,13 @@ mod tests {
         } else {
             panic!("Expected Trade event");
         }
+        if let StreamEvent::Trade(trade) = &result[1] {
+            assert_eq!(trade.tid, 12346);
+            assert_eq!(trade.side, Side::Sell);
+            assert_eq!(*trade.qty, 0.5);
+        } else {
+            panic!("Expected Trade event");
+        }
     }
 
     #[test]
@@ -548,6 +1930,17 @@ mod tests {
                 },
                 "status": "open",
                 "statusTimestamp": 1234567891
+            }, {
+                "order": {
+                    "coin": "ETH",
+                    "side": "A",
+                    "limitPx": "3000.0",
+                    "sz": "0.1",
+                    "oid": 12346,
+                    "timestamp": 1234567890
+                },
+                "status": "canceled",
+                "statusTimestamp": 1234567892
             }]
         }"#;
         let mut bytes = mock_message_str.as_bytes().to_vec();
@@ -555,7 +1948,15 @@ mod tests {
 
         let result = HlWs::<DummyTransport>::parse_orders_simd(&mock_message).unwrap();
 
-        if let Some(StreamEvent::Order(order_event)) = result {
+        assert_eq!(result.len(), 2);
+        if let StreamEvent::Order(order_event) = &result[1] {
+            assert_eq!(order_event.coin, "ETH");
+            assert_eq!(order_event.oid, 12346);
+            assert_eq!(order_event.status, "canceled");
+        } else {
+            panic!("Expected Order event");
+        }
+        if let StreamEvent::Order(order_event) = &result[0] {
             assert_eq!(order_event.coin, "BTC");
             assert_eq!(order_event.side, "B");
             assert_eq!(order_event.limit_px, "50000.0");
@@ -583,6 +1984,16 @@ mod tests {
                     "time": 1234567890,
                     "fee": "0.50",
                     "hash": "abcdef123456"
+                }, {
+                    "coin": "BTC",
+                    "side": "B",
+                    "px": "50001.0",
+                    "sz": "0.002",
+                    "oid": 12345,
+                    "tid": 67891,
+                    "time": 1234567890,
+                    "fee": "1.00",
+                    "hash": "abcdef123456"
                 }]
             }
         }"#;
@@ -591,7 +2002,14 @@ mod tests {
 
         let result = HlWs::<DummyTransport>::parse_fills_simd(&mock_message).unwrap();
 
-        if let Some(StreamEvent::Fill(fill_event)) = result {
+        assert_eq!(result.len(), 2);
+        if let StreamEvent::Fill(fill_event) = &result[1] {
+            assert_eq!(fill_event.tid, 67891);
+            assert_eq!(fill_event.px, "50001.0");
+        } else {
+            panic!("Expected Fill event");
+        }
+        if let StreamEvent::Fill(fill_event) = &result[0] {
             assert_eq!(fill_event.coin, "BTC");
             assert_eq!(fill_event.side, "B");
             assert_eq!(fill_event.px, "50000.0");
@@ -601,9 +2019,394 @@ mod tests {
             assert_eq!(fill_event.time, 1234567890);
             assert_eq!(fill_event.fee, "0.50");
             assert_eq!(fill_event.hash, "abcdef123456");
+            assert!(!fill_event.is_snapshot);
         } else {
             panic!("Expected Fill event");
         }
+
+        // The first message replays history and is flagged as a snapshot
+        let mut bytes = br#"{"data":{"isSnapshot":true,"user":"0x1234567890abcdef1234567890abcdef12345678","fills":[{"coin":"ETH","side":"A","px":"3000.0","sz":"1","oid":1,"tid":2,"time":3,"fee":"0.1","hash":"0xabc"}]}}"#.to_vec();
+        let snapshot = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_fills_simd(&snapshot)
+            .unwrap()
+            .as_slice()
+        {
+            [StreamEvent::Fill(fill)] => assert!(fill.is_snapshot),
+            other => panic!("Expected one Fill event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_twap_slice_fills_parsing() {
+        let mock_message_str = r#"{
+            "channel": "userTwapSliceFills",
+            "data": {
+                "isSnapshot": true,
+                "user": "0x1234567890abcdef1234567890abcdef12345678",
+                "twapSliceFills": [{
+                    "fill": {
+                        "coin": "AVAX",
+                        "side": "B",
+                        "px": "18.435",
+                        "sz": "93.53",
+                        "oid": 90542681,
+                        "tid": 118906512037719,
+                        "time": 1681222254710,
+                        "fee": "0.0",
+                        "hash": "0x0000000000000000000000000000000000000000000000000000000000000000",
+                        "startPosition": "26.86",
+                        "dir": "Open Long",
+                        "closedPnl": "0.0",
+                        "crossed": true,
+                        "feeToken": "USDC"
+                    },
+                    "twapId": 3156
+                }]
+            }
+        }"#;
+        let mut bytes = mock_message_str.as_bytes().to_vec();
+        let mock_message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+
+        let result = HlWs::<DummyTransport>::parse_twap_slice_fills_simd(&mock_message).unwrap();
+
+        if let [StreamEvent::TwapSliceFill { twap_id, fill }] = result.as_slice() {
+            assert_eq!(*twap_id, 3156);
+            assert!(fill.is_snapshot);
+            assert_eq!(fill.coin, "AVAX");
+            assert_eq!(fill.px, "18.435");
+            assert_eq!(fill.sz, "93.53");
+            assert_eq!(fill.oid, 90542681);
+            assert_eq!(fill.user, "0x1234567890abcdef1234567890abcdef12345678");
+        } else {
+            panic!("Expected TwapSliceFill event");
+        }
+    }
+
+    #[test]
+    fn test_all_mids_parsing() {
+        let mut bytes =
+            r#"{"channel": "allMids", "data": {"mids": {"BTC": "50000.5", "@107": "21.3"}}}"#
+                .as_bytes()
+                .to_vec();
+        let message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_all_mids_simd(&message).unwrap() {
+            Some(StreamEvent::AllMids(all_mids)) => {
+                assert_eq!(all_mids.mids.len(), 2);
+                assert_eq!(all_mids.mids["BTC"], "50000.5");
+                assert_eq!(all_mids.mids["@107"], "21.3");
+            }
+            other => panic!("Expected AllMids event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_candle_parsing() {
+        let mut bytes = r#"{
+            "channel": "candle",
+            "data": {
+                "t": 1700000000000, "T": 1700000059999, "s": "BTC", "i": "1m",
+                "o": "50000.0", "c": "50010.0", "h": "50020.0", "l": "49990.0",
+                "v": "12.5", "n": 42
+            }
+        }"#
+        .as_bytes()
+        .to_vec();
+        let message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        assert_eq!(message_key(&message).as_deref(), Some("BTC 1m"));
+        match HlWs::<DummyTransport>::parse_candle_simd(&message).unwrap() {
+            Some(StreamEvent::Candle {
+                coin,
+                interval,
+                candle,
+            }) => {
+                assert_eq!(coin, "BTC");
+                assert_eq!(interval, CandleInterval::Min1);
+                assert_eq!(candle.time, 1700000000000);
+                assert_eq!(candle.open, "50000.0");
+                assert_eq!(candle.high, "50020.0");
+                assert_eq!(candle.low, "49990.0");
+                assert_eq!(candle.close, "50010.0");
+                assert_eq!(candle.volume, "12.5");
+            }
+            other => panic!("Expected Candle event, got {:?}", other),
+        }
+
+        // Subscriptions to other intervals of the same coin are not matched
+        let subscription = json!({"type": "candle", "coin": "BTC", "interval": "1h"});
+        assert_eq!(subscription_key(&subscription).as_deref(), Some("BTC 1h"));
+    }
+
+    #[test]
+    fn test_notification_parsing() {
+        let mut bytes =
+            r#"{"channel": "notification", "data": {"notification": "Order \"1\" filled"}}"#
+                .as_bytes()
+                .to_vec();
+        let message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_notification_simd(&message).unwrap() {
+            Some(StreamEvent::Notification { message }) => {
+                assert_eq!(message, "Order \"1\" filled")
+            }
+            other => panic!("Expected Notification event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_web_data2_parsing() {
+        let mut bytes = r#"{
+            "channel": "webData2",
+            "data": {
+                "clearinghouseState": {
+                    "marginSummary": {"accountValue": "1000.5", "totalNtlPos": "500.0", "totalRawUsd": "1500.5", "totalMarginUsed": "50.0"},
+                    "crossMarginSummary": {"accountValue": "1000.5", "totalNtlPos": "500.0", "totalRawUsd": "1500.5", "totalMarginUsed": "50.0"},
+                    "crossMaintenanceMarginUsed": "10.0",
+                    "withdrawable": "950.5",
+                    "assetPositions": [{
+                        "type": "oneWay",
+                        "position": {
+                            "coin": "ETH", "szi": "-0.5", "entryPx": "3000.0",
+                            "positionValue": "1500.0", "unrealizedPnl": "12.5",
+                            "returnOnEquity": "0.1", "liquidationPx": null,
+                            "leverage": {"type": "cross", "value": 10},
+                            "marginUsed": "150.0", "maxLeverage": 50
+                        }
+                    }],
+                    "time": 1700000000000
+                },
+                "openOrders": [{
+                    "coin": "BTC", "side": "B", "limitPx": "49000.0", "sz": "0.1",
+                    "oid": 77, "timestamp": 1699999999000, "origSz": "0.2",
+                    "cloid": "0x00000000000000000000000000000001"
+                }],
+                "serverTime": 1700000000123,
+                "isVault": false,
+                "user": "0x1234567890abcdef1234567890abcdef12345678"
+            }
+        }"#.as_bytes().to_vec();
+        let message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_web_data2_simd(&message).unwrap() {
+            Some(StreamEvent::WebData2(web_data)) => {
+                assert_eq!(web_data.user, "0x1234567890abcdef1234567890abcdef12345678");
+                assert_eq!(web_data.server_time, 1700000000123);
+                assert_eq!(web_data.account_value, "1000.5");
+                assert_eq!(web_data.total_margin_used, "50.0");
+                assert_eq!(web_data.withdrawable, "950.5");
+                assert_eq!(web_data.positions.len(), 1);
+                assert_eq!(web_data.positions[0].coin, "ETH");
+                assert_eq!(web_data.positions[0].size, -0.5);
+                assert_eq!(web_data.positions[0].entry_px, Some(3000.0));
+                assert_eq!(web_data.positions[0].unrealized_pnl, 12.5);
+                assert_eq!(web_data.open_orders.len(), 1);
+                assert_eq!(web_data.open_orders[0].oid, 77);
+                assert_eq!(web_data.open_orders[0].orig_sz, "0.2");
+                assert_eq!(
+                    web_data.open_orders[0].cloid.as_deref(),
+                    Some("0x00000000000000000000000000000001")
+                );
+            }
+            other => panic!("Expected WebData2 event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_user_events_parsing() {
+        let user = "1234567890ABCDEF1234567890abcdef12345678";
+        let mut bytes = r#"{"channel": "user", "data": {"fills": [
+                {"coin": "BTC", "px": "50000.0", "sz": "0.1", "side": "A", "time": 1, "startPosition": "0.1", "dir": "Close Long", "closedPnl": "5.0", "hash": "0xabc", "oid": 10, "crossed": true, "fee": "0.5", "tid": 20, "feeToken": "USDC"},
+                {"coin": "BTC", "px": "50001.0", "sz": "0.2", "side": "A", "time": 1, "startPosition": "0.0", "dir": "Open Short", "closedPnl": "0.0", "hash": "0xabc", "oid": 10, "crossed": true, "fee": "1.0", "tid": 21, "feeToken": "USDC"}
+            ]}}"#.as_bytes().to_vec();
+        let fills = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        let events = HlWs::<DummyTransport>::parse_user_events_simd(&fills, user).unwrap();
+        assert_eq!(events.len(), 2);
+        match &events[1] {
+            StreamEvent::Fill(fill) => {
+                assert_eq!(fill.tid, 21);
+                assert_eq!(fill.user, "0x1234567890abcdef1234567890abcdef12345678");
+                assert!(!fill.is_snapshot);
+            }
+            other => panic!("Expected Fill event, got {:?}", other),
+        }
+
+        let mut bytes = r#"{"channel": "user", "data": {"funding": {"time": 1700000000000, "coin": "ETH", "usdc": "-0.25", "szi": "1.0", "fundingRate": "0.0000125"}}}"#.as_bytes().to_vec();
+        let funding = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_user_events_simd(&funding, user)
+            .unwrap()
+            .as_slice()
+        {
+            [StreamEvent::Funding(funding)] => {
+                assert_eq!(funding.coin, "ETH");
+                assert_eq!(funding.usdc, "-0.25");
+                assert_eq!(funding.funding_rate, "0.0000125");
+            }
+            other => panic!("Expected Funding event, got {:?}", other),
+        }
+
+        let mut bytes = r#"{"channel": "user", "data": {"liquidation": {"lid": 5, "liquidator": "0xaaa", "liquidatedUser": "0xbbb", "liquidatedNtlPos": "1000.0", "liquidatedAccountValue": "20.0"}}}"#.as_bytes().to_vec();
+        let liquidation = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_user_events_simd(&liquidation, user)
+            .unwrap()
+            .as_slice()
+        {
+            [StreamEvent::Liquidation(liquidation)] => {
+                assert_eq!(liquidation.lid, 5);
+                assert_eq!(liquidation.liquidated_user, "0xbbb");
+                assert_eq!(liquidation.liquidated_account_value, "20.0");
+            }
+            other => panic!("Expected Liquidation event, got {:?}", other),
+        }
+
+        let mut bytes = r#"{"channel": "user", "data": {"nonUserCancel": [{"coin": "SOL", "oid": 1}, {"coin": "SOL", "oid": 2}]}}"#.as_bytes().to_vec();
+        let cancels = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_user_events_simd(&cancels, user)
+            .unwrap()
+            .as_slice()
+        {
+            [StreamEvent::NonUserCancel { coin, oid: 1 }, StreamEvent::NonUserCancel { oid: 2, .. }] =>
+            {
+                assert_eq!(coin, "SOL")
+            }
+            other => panic!("Expected NonUserCancel events, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_user_fundings_parsing() {
+        let mut bytes = r#"{"channel": "userFundings", "data": {"isSnapshot": true, "user": "0x1234567890abcdef1234567890abcdef12345678", "fundings": [
+                {"time": 1, "coin": "BTC", "usdc": "-1.5", "szi": "0.1", "fundingRate": "0.0001", "nSamples": null},
+                {"time": 2, "coin": "ETH", "usdc": "0.3", "szi": "-2.0", "fundingRate": "0.00002", "nSamples": null}
+            ]}}"#.as_bytes().to_vec();
+        let message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        let events = HlWs::<DummyTransport>::parse_user_fundings_simd(&message).unwrap();
+        assert_eq!(events.len(), 2);
+        match &events[1] {
+            StreamEvent::Funding(funding) => {
+                assert_eq!(funding.coin, "ETH");
+                assert_eq!(funding.szi, "-2.0");
+                assert_eq!(funding.time, 2);
+                assert!(funding.is_snapshot);
+            }
+            other => panic!("Expected Funding event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_ledger_updates_parsing() {
+        let mut bytes = r#"{"channel": "userNonFundingLedgerUpdates", "data": {"user": "0x1234567890abcdef1234567890abcdef12345678", "nonFundingLedgerUpdates": [
+                {"time": 1, "hash": "0xaaa", "delta": {"type": "deposit", "usdc": "100.0"}},
+                {"time": 2, "hash": "0xbbb", "delta": {"type": "spotTransfer", "token": "PURR", "amount": "5", "usdcValue": "1.0", "user": "0x1", "destination": "0x2", "fee": "0"}}
+            ]}}"#.as_bytes().to_vec();
+        let message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_ledger_updates_simd(&message)
+            .unwrap()
+            .as_slice()
+        {
+            [StreamEvent::LedgerUpdate(deposit), StreamEvent::LedgerUpdate(transfer)] => {
+                assert_eq!(deposit.kind, "deposit");
+                assert_eq!(deposit.usdc.as_deref(), Some("100.0"));
+                assert_eq!(deposit.hash, "0xaaa");
+                assert!(!deposit.is_snapshot);
+                assert_eq!(transfer.kind, "spotTransfer");
+                assert_eq!(transfer.usdc, None);
+                assert_eq!(transfer.delta["token"], "PURR");
+            }
+            other => panic!("Expected two LedgerUpdate events, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_active_asset_ctx_parsing() {
+        let mut bytes = r#"{"channel": "activeAssetCtx", "data": {"coin": "BTC", "ctx": {
+                "dayNtlVlm": "1000000.0", "prevDayPx": "49000.0", "markPx": "50000.0", "midPx": "50000.5",
+                "funding": "0.0000125", "openInterest": "1234.5", "oraclePx": "50001.0",
+                "premium": "0.0001", "impactPxs": ["49999.0", "50002.0"], "dayBaseVlm": "20.0"
+            }}}"#.as_bytes().to_vec();
+        let perp = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_active_asset_ctx_simd(&perp).unwrap() {
+            Some(StreamEvent::ActiveAssetCtx { coin, ctx }) => {
+                assert_eq!(coin, "BTC");
+                assert_eq!(ctx.mark_px, "50000.0");
+                assert_eq!(ctx.open_interest, "1234.5");
+                assert_eq!(ctx.impact_pxs.as_ref().map(Vec::len), Some(2));
+            }
+            other => panic!("Expected ActiveAssetCtx event, got {:?}", other),
+        }
+
+        let mut bytes = r#"{"channel": "activeSpotAssetCtx", "data": {"coin": "@107", "ctx": {
+                "dayNtlVlm": "5000.0", "prevDayPx": "20.0", "markPx": "21.0", "midPx": "21.05",
+                "circulatingSupply": "1000000.0"
+            }}}"#
+            .as_bytes()
+            .to_vec();
+        let spot = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_active_asset_ctx_simd(&spot).unwrap() {
+            Some(StreamEvent::ActiveSpotAssetCtx { coin, ctx }) => {
+                assert_eq!(coin, "@107");
+                assert_eq!(ctx.mark_px.as_deref(), Some("21.0"));
+                assert_eq!(ctx.circulating_supply, "1000000.0");
+            }
+            other => panic!("Expected ActiveSpotAssetCtx event, got {:?}", other),
+        }
+
+        // Spot contexts are routed to activeAssetCtx subscribers
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let (sub, _state) = Subscriber::new(
+            0,
+            StreamKind::ActiveAssetCtx,
+            json!({"type": "activeAssetCtx", "coin": "@107"}),
+            tx,
+        );
+        assert!(sub.matches("activeSpotAssetCtx", message_key(&spot).as_deref()));
+    }
+
+    #[test]
+    fn test_active_asset_data_parsing() {
+        let mut bytes = r#"{"channel": "activeAssetData", "data": {
+                "user": "0x1234567890abcdef1234567890abcdef12345678", "coin": "ETH",
+                "leverage": {"type": "isolated", "value": 5, "rawUsd": "-100.0"},
+                "maxTradeSzs": ["10.0", "12.0"], "availableToTrade": ["30000.0", "36000.0"],
+                "markPx": "3000.0"
+            }}"#
+        .as_bytes()
+        .to_vec();
+        let message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_active_asset_data_simd(&message).unwrap() {
+            Some(StreamEvent::ActiveAssetData(data)) => {
+                assert_eq!(data.coin, "ETH");
+                assert_eq!(data.leverage.kind, LeverageType::Isolated);
+                assert_eq!(data.leverage.value, 5);
+                assert_eq!(data.max_trade_szs, vec!["10.0", "12.0"]);
+                assert_eq!(data.available_to_trade, vec!["30000.0", "36000.0"]);
+                assert_eq!(data.mark_px.as_deref(), Some("3000.0"));
+            }
+            other => panic!("Expected ActiveAssetData event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_twap_history_parsing() {
+        let mut bytes = r#"{"channel": "userTwapHistory", "data": {"isSnapshot": true, "user": "0x1234567890abcdef1234567890abcdef12345678", "history": [{
+                "time": 1700000000,
+                "state": {
+                    "coin": "AVAX", "user": "0x1234567890abcdef1234567890abcdef12345678", "side": "B",
+                    "sz": "100.0", "executedSz": "40.0", "executedNtl": "740.0", "minutes": 30,
+                    "reduceOnly": false, "randomize": true, "timestamp": 1699999000000
+                },
+                "status": {"status": "activated"}
+            }]}}"#.as_bytes().to_vec();
+        let message = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        match HlWs::<DummyTransport>::parse_twap_history_simd(&message)
+            .unwrap()
+            .as_slice()
+        {
+            [StreamEvent::TwapHistory { entry, is_snapshot }] => {
+                assert!(*is_snapshot);
+                assert_eq!(entry.state.coin, "AVAX");
+                assert_eq!(entry.state.executed_sz, "40.0");
+                assert_eq!(entry.status.status, "activated");
+                assert_eq!(entry.twap_id, None);
+            }
+            other => panic!("Expected TwapHistory event, got {:?}", other),
+        }
     }
 
     #[test]
@@ -634,10 +2437,913 @@ mod tests {
             "subscription": { "type": "bbo", "coin": "BTC" }
         });
 
-        // This should be filtered out in handle_message
+        // This should be filtered out in dispatch
         assert_eq!(subscription_response["method"], "subscriptionResponse");
     }
 
+    #[test]
+    fn test_routing_keys() {
+        let trades = json!({"type": "trades", "coin": "BTC"});
+        let fills =
+            json!({"type": "userFills", "user": "ABCDEF0123456789abcdef0123456789ABCDEF01"});
+        assert_eq!(subscription_key(&trades).as_deref(), Some("BTC"));
+        assert_eq!(
+            subscription_key(&fills).as_deref(),
+            Some("abcdef0123456789abcdef0123456789abcdef01")
+        );
+
+        let mut bytes = br#"{"channel":"trades","data":[{"coin":"BTC","px":"1"}]}"#.to_vec();
+        let val = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        assert_eq!(message_key(&val).as_deref(), Some("BTC"));
+
+        let mut bytes = br#"{"channel":"userFills","data":{"user":"0xABCDEF0123456789abcdef0123456789abcdef01","fills":[]}}"#.to_vec();
+        let val = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        assert_eq!(
+            message_key(&val).as_deref(),
+            Some("abcdef0123456789abcdef0123456789abcdef01")
+        );
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let (sub, _state) = Subscriber::new(0, StreamKind::Trades, trades, tx);
+        assert!(sub.matches("trades", Some("BTC")));
+        assert!(!sub.matches("trades", Some("ETH")));
+        assert!(!sub.matches("bbo", Some("BTC")));
+        // Unkeyed messages match every subscriber of the channel
+        assert!(sub.matches("trades", None));
+
+        // Per-user asset data is keyed by user and coin
+        let asset_data = json!({"type": "activeAssetData", "user": "0xABCDEF0123456789abcdef0123456789abcdef01", "coin": "BTC"});
+        assert_eq!(
+            subscription_key(&asset_data).as_deref(),
+            Some("abcdef0123456789abcdef0123456789abcdef01 BTC")
+        );
+        let mut bytes = br#"{"channel":"activeAssetData","data":{"user":"0xabcdef0123456789abcdef0123456789abcdef01","coin":"BTC"}}"#.to_vec();
+        let val = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        assert_eq!(
+            message_key(&val).as_deref(),
+            Some("abcdef0123456789abcdef0123456789abcdef01 BTC")
+        );
+
+        // Order updates carry no user
+        let mut bytes =
+            br#"{"channel":"orderUpdates","data":[{"order":{"coin":"BTC"},"status":"open"}]}"#
+                .to_vec();
+        let val = simd_json::to_borrowed_value(&mut bytes).unwrap();
+        assert_eq!(message_key(&val), None);
+    }
+
+    fn trade_message(coin: &str, tid: u64) -> Vec<u8> {
+        json!({
+            "channel": "trades",
+            "data": [{
+                "coin": coin,
+                "side": "B",
+                "px": "100.5",
+                "sz": "1",
+                "time": 1,
+                "hash": "0xabc",
+                "tid": tid
+            }]
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Next trade, skipping lifecycle events
+    async fn next_trade(rx: &mut mpsc::UnboundedReceiver<StreamEvent>) -> Trade {
+        loop {
+            match rx.recv().await {
+                Some(StreamEvent::Trade(trade)) => return trade,
+                Some(
+                    StreamEvent::Connected
+                    | StreamEvent::Disconnected { .. }
+                    | StreamEvent::Resubscribed,
+                ) => {}
+                other => panic!("Expected Trade event, got {:?}", other),
+            }
+        }
+    }
+
+    /// Next subscription the client sent, acknowledged like the exchange does
+    async fn subscribed(server: &mut MockServer) -> Value {
+        let msg: Value = serde_json::from_str(&server.sent.recv().await.unwrap()).unwrap();
+        assert_eq!(msg["method"], "subscribe");
+        let ack = json!({"channel": "subscriptionResponse", "data": msg});
+        server.send(ack.to_string().into_bytes());
+        msg["subscription"].clone()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shared_connection_routing_and_replay() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport.clone(), false);
+
+        let (btc_tx, mut btc_rx) = mpsc::unbounded_channel();
+        let (btc2_tx, mut btc2_rx) = mpsc::unbounded_channel();
+        let (eth_tx, mut eth_rx) = mpsc::unbounded_channel();
+        let _btc = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), btc_tx, None)
+            .await
+            .unwrap();
+        let _btc2 = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), btc2_tx, None)
+            .await
+            .unwrap();
+        let _eth = ws
+            .subscribe(StreamKind::Trades, Some("ETH"), eth_tx, None)
+            .await
+            .unwrap();
+
+        // One socket, and a duplicate subscription is only sent once
+        let mut server = servers.recv().await.unwrap();
+        assert_eq!(subscribed(&mut server).await["coin"], "BTC");
+        assert_eq!(subscribed(&mut server).await["coin"], "ETH");
+
+        server.send(trade_message("ETH", 1));
+        server.send(trade_message("BTC", 2));
+        assert_eq!(next_trade(&mut eth_rx).await.tid, 1);
+        assert_eq!(next_trade(&mut btc_rx).await.tid, 2);
+        assert_eq!(next_trade(&mut btc2_rx).await.tid, 2);
+        assert!(eth_rx.try_recv().is_err());
+
+        // Dropping the socket reconnects and replays every subscription
+        drop(server);
+        let mut server = servers.recv().await.unwrap();
+        let mut coins = vec![
+            subscribed(&mut server).await["coin"].clone(),
+            subscribed(&mut server).await["coin"].clone(),
+        ];
+        coins.sort_by_key(|coin| coin.to_string());
+        assert_eq!(coins, vec![json!("BTC"), json!("ETH")]);
+
+        server.send(trade_message("BTC", 3));
+        assert_eq!(next_trade(&mut btc_rx).await.tid, 3);
+        assert_eq!(transport.connects(), 2);
+    }
+
+    fn order_update(oid: u64) -> Vec<u8> {
+        json!({
+            "channel": "orderUpdates",
+            "data": [{
+                "order": {"coin": "BTC", "side": "B", "limitPx": "100", "sz": "1", "oid": oid, "timestamp": 1},
+                "status": "open",
+                "statusTimestamp": 1
+            }]
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Next order's id, skipping lifecycle events
+    async fn next_oid(rx: &mut mpsc::UnboundedReceiver<StreamEvent>) -> u64 {
+        loop {
+            match rx.recv().await {
+                Some(StreamEvent::Order(order)) => return order.oid,
+                Some(StreamEvent::Connected) => {}
+                other => panic!("Expected Order event, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_user_stream_routing() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport, false);
+        let alice = "0x1111111111111111111111111111111111111111";
+        let bob = "0x2222222222222222222222222222222222222222";
+
+        let (alice_tx, mut alice_rx) = mpsc::unbounded_channel();
+        let (bob_tx, mut bob_rx) = mpsc::unbounded_channel();
+        let alice_orders = ws
+            .subscribe(StreamKind::Orders, None, alice_tx.clone(), Some(alice))
+            .await
+            .unwrap();
+        let bob_orders = ws
+            .subscribe(StreamKind::Orders, None, bob_tx.clone(), Some(bob))
+            .await
+            .unwrap();
+        let mut server = servers.recv().await.unwrap();
+        assert_eq!(subscribed(&mut server).await["user"], alice);
+        assert_eq!(subscribed(&mut server).await["user"], bob);
+
+        // Order updates name no user, so with two users they reach neither
+        server.send(order_update(1));
+
+        // Fills name their user and reach only that user's subscription
+        let _alice_fills = ws
+            .subscribe(StreamKind::Fills, None, alice_tx, Some(alice))
+            .await
+            .unwrap();
+        let _bob_fills = ws
+            .subscribe(StreamKind::Fills, None, bob_tx, Some(bob))
+            .await
+            .unwrap();
+        assert_eq!(subscribed(&mut server).await["user"], alice);
+        assert_eq!(subscribed(&mut server).await["user"], bob);
+        let fill = json!({
+            "channel": "userFills",
+            "data": {"user": bob, "fills": [{
+                "coin": "BTC", "side": "B", "px": "100", "sz": "1", "oid": 3,
+                "tid": 7, "time": 1, "fee": "0.1", "hash": "0xabc"
+            }]}
+        });
+        server.send(fill.to_string().into_bytes());
+
+        // With a single user left, its order updates are delivered
+        drop(bob_orders);
+        assert_eq!(unsubscribed(&mut server).await["user"], bob);
+        server.send(order_update(2));
+        assert_eq!(next_oid(&mut alice_rx).await, 2);
+        assert!(alice_rx.try_recv().is_err());
+
+        let mut bob_events = Vec::new();
+        while let Ok(event) = bob_rx.try_recv() {
+            bob_events.push(event);
+        }
+        assert!(matches!(
+            bob_events.as_slice(),
+            [StreamEvent::Connected, StreamEvent::Connected, StreamEvent::Fill(fill)] if fill.tid == 7
+        ));
+        drop(alice_orders);
+    }
+
+    async fn unsubscribed(server: &mut MockServer) -> Value {
+        let msg: Value = serde_json::from_str(&server.sent.recv().await.unwrap()).unwrap();
+        assert_eq!(msg["method"], "unsubscribe");
+        msg["subscription"].clone()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_aggregated_l2_book_subscription() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport, false);
+        let (tx, _rx) = mpsc::unbounded_channel();
+
+        let invalid = BookAggregation::sig_figs(4).with_mantissa(2);
+        let err = ws
+            .subscribe(
+                StreamKind::L2BookAggregated(invalid),
+                Some("BTC"),
+                tx.clone(),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DexError::Validation(_)));
+
+        let aggregation = BookAggregation::sig_figs(5).with_mantissa(5);
+        let _book = ws
+            .subscribe(
+                StreamKind::L2BookAggregated(aggregation),
+                Some("BTC"),
+                tx,
+                None,
+            )
+            .await
+            .unwrap();
+        let mut server = servers.recv().await.unwrap();
+        assert_eq!(
+            subscribed(&mut server).await,
+            json!({"type": "l2Book", "coin": "BTC", "nSigFigs": 5, "mantissa": 5})
+        );
+    }
+
+    fn book_message(coin: &str, px: &str) -> Vec<u8> {
+        json!({
+            "channel": "l2Book",
+            "data": {
+                "coin": coin,
+                "time": 1,
+                "levels": [[{"px": px, "sz": "1", "n": 1}], []]
+            }
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Next book's best bid, skipping lifecycle events
+    async fn next_bid(rx: &mut mpsc::UnboundedReceiver<StreamEvent>) -> f64 {
+        loop {
+            match rx.recv().await {
+                Some(StreamEvent::L2(book)) => return *book.bids[0].price,
+                Some(StreamEvent::Connected) => {}
+                other => panic!("Expected L2 event, got {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_conflicting_l2_book_aggregations() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport, false);
+        let coarse = StreamKind::L2BookAggregated(BookAggregation::sig_figs(2));
+
+        let (full_tx, mut full_rx) = mpsc::unbounded_channel();
+        let (coarse_tx, mut coarse_rx) = mpsc::unbounded_channel();
+        let (eth_tx, mut eth_rx) = mpsc::unbounded_channel();
+        let full = ws
+            .subscribe(StreamKind::L2Book, Some("BTC"), full_tx.clone(), None)
+            .await
+            .unwrap();
+        let err = ws
+            .subscribe(coarse, Some("BTC"), coarse_tx.clone(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DexError::Validation(_)));
+        // The same aggregation, and other coins, are unaffected
+        let full2 = ws
+            .subscribe(StreamKind::L2Book, Some("BTC"), full_tx, None)
+            .await
+            .unwrap();
+        let _eth = ws
+            .subscribe(coarse, Some("ETH"), eth_tx, None)
+            .await
+            .unwrap();
+
+        let mut server = servers.recv().await.unwrap();
+        assert_eq!(
+            subscribed(&mut server).await,
+            json!({"type": "l2Book", "coin": "BTC"})
+        );
+        assert_eq!(subscribed(&mut server).await["coin"], "ETH");
+        server.send(book_message("BTC", "65001.5"));
+        server.send(book_message("ETH", "3400"));
+        assert_eq!(next_bid(&mut full_rx).await, 65001.5);
+        assert_eq!(next_bid(&mut full_rx).await, 65001.5);
+        assert_eq!(next_bid(&mut eth_rx).await, 3400.0);
+
+        // Once every full-depth handle is gone the coin can switch aggregation
+        drop(full);
+        drop(full2);
+        assert_eq!(unsubscribed(&mut server).await["coin"], "BTC");
+        let _coarse = ws
+            .subscribe(coarse, Some("BTC"), coarse_tx, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            subscribed(&mut server).await,
+            json!({"type": "l2Book", "coin": "BTC", "nSigFigs": 2})
+        );
+        server.send(book_message("BTC", "65000"));
+        assert_eq!(next_bid(&mut coarse_rx).await, 65000.0);
+        assert!(full_rx.try_recv().is_err());
+        assert!(coarse_rx.try_recv().is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_subscription_goes_live_on_ack() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport, false);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let handle = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), tx, None)
+            .await
+            .unwrap();
+
+        // Written but rejected: not live
+        let mut server = servers.recv().await.unwrap();
+        let msg: Value = serde_json::from_str(&server.sent.recv().await.unwrap()).unwrap();
+        assert_eq!(msg["method"], "subscribe");
+        server.send(br#"{"channel":"error","data":"Invalid subscription"}"#.to_vec());
+        sleep(Duration::from_secs(1)).await;
+        assert!(rx.try_recv().is_err());
+        assert_eq!(handle.state(), ConnectionState::Connecting);
+
+        let ack = json!({"channel": "subscriptionResponse", "data": msg});
+        server.send(ack.to_string().into_bytes());
+        assert!(matches!(rx.recv().await, Some(StreamEvent::Connected)));
+        assert_eq!(handle.state(), ConnectionState::Connected);
+    }
+
+    fn fill_message(user: &str, tid: u64, is_snapshot: bool) -> Vec<u8> {
+        json!({
+            "channel": "userFills",
+            "data": {"user": user, "isSnapshot": is_snapshot, "fills": [{
+                "coin": "BTC", "side": "B", "px": "100", "sz": "1", "oid": 3,
+                "tid": tid, "time": 1, "fee": "0.1", "hash": "0xabc"
+            }]}
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    /// Events already delivered to `rx`
+    fn drain(rx: &mut mpsc::UnboundedReceiver<StreamEvent>) -> Vec<StreamEvent> {
+        std::iter::from_fn(|| rx.try_recv().ok()).collect()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_late_joiners_replay_snapshots() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport, false);
+        let user = "0x1111111111111111111111111111111111111111";
+
+        let (book_tx, mut book_rx) = mpsc::unbounded_channel();
+        let (fills_tx, mut fills_rx) = mpsc::unbounded_channel();
+        let (trades_tx, mut trades_rx) = mpsc::unbounded_channel();
+        let _book = ws
+            .subscribe(StreamKind::L2Book, Some("BTC"), book_tx, None)
+            .await
+            .unwrap();
+        let _fills = ws
+            .subscribe(StreamKind::Fills, None, fills_tx, Some(user))
+            .await
+            .unwrap();
+        let _trades = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), trades_tx, None)
+            .await
+            .unwrap();
+        let mut server = servers.recv().await.unwrap();
+        for _ in 0..3 {
+            subscribed(&mut server).await;
+        }
+        server.send(book_message("BTC", "65000"));
+        server.send(book_message("BTC", "65001"));
+        server.send(fill_message(user, 1, true));
+        server.send(fill_message(user, 2, false));
+        server.send(trade_message("BTC", 1));
+        assert_eq!(next_bid(&mut book_rx).await, 65000.0);
+        assert_eq!(next_bid(&mut book_rx).await, 65001.0);
+        assert_eq!(next_trade(&mut trades_rx).await.tid, 1);
+        assert_eq!(drain(&mut fills_rx).len(), 3);
+
+        // Joiners get the latest book, the fill snapshot and later fills, and no trades
+        let (book2_tx, mut book2_rx) = mpsc::unbounded_channel();
+        let (fills2_tx, mut fills2_rx) = mpsc::unbounded_channel();
+        let (trades2_tx, mut trades2_rx) = mpsc::unbounded_channel();
+        let book2 = ws
+            .subscribe(StreamKind::L2Book, Some("BTC"), book2_tx, None)
+            .await
+            .unwrap();
+        let _fills2 = ws
+            .subscribe(StreamKind::Fills, None, fills2_tx, Some(user))
+            .await
+            .unwrap();
+        let _trades2 = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), trades2_tx, None)
+            .await
+            .unwrap();
+        book2
+            .state_updates()
+            .wait_for(|state| *state == ConnectionState::Connected)
+            .await
+            .unwrap();
+        sleep(Duration::from_secs(1)).await;
+
+        assert!(matches!(
+            drain(&mut book2_rx).as_slice(),
+            [StreamEvent::Connected, StreamEvent::L2(book)] if *book.bids[0].price == 65001.0
+        ));
+        let fills: Vec<(u64, bool)> = drain(&mut fills2_rx)
+            .into_iter()
+            .filter_map(|event| match event {
+                StreamEvent::Fill(fill) => Some((fill.tid, fill.is_snapshot)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fills, vec![(1, true), (2, false)]);
+        assert!(matches!(
+            drain(&mut trades2_rx).as_slice(),
+            [StreamEvent::Connected]
+        ));
+
+        // The server subscriptions were shared, not sent again
+        while let Ok(msg) = server.sent.try_recv() {
+            assert_ne!(
+                serde_json::from_str::<Value>(&msg).unwrap()["method"],
+                "subscribe"
+            );
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_unsubscribe_and_state() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport.clone(), false);
+
+        let (btc_tx, _btc_rx) = mpsc::unbounded_channel();
+        let (btc2_tx, mut btc2_rx) = mpsc::unbounded_channel();
+        let (eth_tx, _eth_rx) = mpsc::unbounded_channel();
+        let btc = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), btc_tx, None)
+            .await
+            .unwrap();
+        let btc2 = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), btc2_tx, None)
+            .await
+            .unwrap();
+        let eth = ws
+            .subscribe(StreamKind::Trades, Some("ETH"), eth_tx, None)
+            .await
+            .unwrap();
+
+        let mut server = servers.recv().await.unwrap();
+        subscribed(&mut server).await;
+        subscribed(&mut server).await;
+        let mut updates = btc.state_updates();
+        updates
+            .wait_for(|state| *state == ConnectionState::Connected)
+            .await
+            .unwrap();
+        eth.state_updates()
+            .wait_for(|state| *state == ConnectionState::Connected)
+            .await
+            .unwrap();
+
+        // A shared subscription is only released by its last handle
+        btc.unsubscribe();
+        drop(eth);
+        assert_eq!(unsubscribed(&mut server).await["coin"], "ETH");
+        server.send(trade_message("BTC", 1));
+        assert_eq!(next_trade(&mut btc2_rx).await.tid, 1);
+
+        // Dropping the last handle unsubscribes and closes the idle socket
+        drop(btc2);
+        assert_eq!(unsubscribed(&mut server).await["coin"], "BTC");
+        assert!(server.sent.recv().await.is_none());
+
+        // A new subscription opens a fresh connection
+        let (sol_tx, _sol_rx) = mpsc::unbounded_channel();
+        let sol = ws
+            .subscribe(StreamKind::Trades, Some("SOL"), sol_tx, None)
+            .await
+            .unwrap();
+        let mut server = servers.recv().await.unwrap();
+        assert_eq!(subscribed(&mut server).await["coin"], "SOL");
+        assert_eq!(transport.connects(), 2);
+
+        sol.unsubscribe();
+        assert_eq!(unsubscribed(&mut server).await["coin"], "SOL");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_heartbeat_reconnects_dead_connection() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport.clone(), false);
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let _btc = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), tx, None)
+            .await
+            .unwrap();
+
+        let mut server = servers.recv().await.unwrap();
+        subscribed(&mut server).await;
+
+        // Answered pings and flowing data keep the connection alive
+        let start = Instant::now();
+        for tid in 0..3 {
+            let msg: Value = serde_json::from_str(&server.sent.recv().await.unwrap()).unwrap();
+            assert_eq!(msg, json!({"method": "ping"}));
+            server.send(br#"{"channel":"pong"}"#.to_vec());
+            server.send(trade_message("BTC", tid));
+        }
+        assert_eq!(start.elapsed(), PING_INTERVAL * 3);
+
+        // A missed pong drops the socket and reconnects
+        let msg: Value = serde_json::from_str(&server.sent.recv().await.unwrap()).unwrap();
+        assert_eq!(msg["method"], "ping");
+        let mut server = servers.recv().await.unwrap();
+        assert_eq!(subscribed(&mut server).await["coin"], "BTC");
+        assert_eq!(transport.connects(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_data_silence_reconnects_despite_pongs() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport.clone(), false);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let _btc = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), tx, None)
+            .await
+            .unwrap();
+
+        let mut server = servers.recv().await.unwrap();
+        subscribed(&mut server).await;
+        assert!(matches!(rx.recv().await, Some(StreamEvent::Connected)));
+
+        // Pongs and post responses arrive, but no subscription data
+        let start = Instant::now();
+        while let Some(msg) = server.sent.recv().await {
+            let msg: Value = serde_json::from_str(&msg).unwrap();
+            assert_eq!(msg["method"], "ping");
+            server.send(br#"{"channel":"pong"}"#.to_vec());
+            server.send(br#"{"channel":"post","data":{"id":99,"response":{}}}"#.to_vec());
+        }
+        match rx.recv().await {
+            Some(StreamEvent::Disconnected { reason }) => {
+                assert!(reason.contains("No data received"))
+            }
+            other => panic!("Expected Disconnected, got {:?}", other),
+        }
+        assert_eq!(start.elapsed(), STALE_TIMEOUT);
+
+        let mut server = servers.recv().await.unwrap();
+        assert_eq!(subscribed(&mut server).await["coin"], "BTC");
+        assert_eq!(transport.connects(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_quiet_user_stream_stays_connected() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport.clone(), false);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let user = "0x1111111111111111111111111111111111111111";
+        let _orders = ws
+            .subscribe(StreamKind::Orders, None, tx, Some(user))
+            .await
+            .unwrap();
+
+        let mut server = servers.recv().await.unwrap();
+        subscribed(&mut server).await;
+        assert!(matches!(rx.recv().await, Some(StreamEvent::Connected)));
+
+        // Answered pings keep a stream without data alive well past the silence timeout
+        let start = Instant::now();
+        while start.elapsed() < STALE_TIMEOUT * 5 {
+            let msg: Value = serde_json::from_str(&server.sent.recv().await.unwrap()).unwrap();
+            assert_eq!(msg["method"], "ping");
+            server.send(br#"{"channel":"pong"}"#.to_vec());
+        }
+        assert!(rx.try_recv().is_err());
+        assert_eq!(transport.connects(), 1);
+    }
+
+    #[test]
+    fn test_reconnect_policy_delay() {
+        let policy = ReconnectPolicy {
+            jitter: 0.0,
+            ..ReconnectPolicy::default()
+        };
+        assert_eq!(policy.delay(1), Duration::from_secs(1));
+        assert_eq!(policy.delay(3), Duration::from_secs(4));
+        assert_eq!(policy.delay(10), Duration::from_secs(30));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(30));
+        assert!(!policy.exhausted(10));
+        assert!(policy.exhausted(11));
+        assert!(!ReconnectPolicy::infinite().exhausted(u32::MAX));
+
+        let jittered = ReconnectPolicy::default();
+        for attempt in 1..8 {
+            let delay = jittered.delay(attempt);
+            assert!(delay >= policy.delay(attempt));
+            assert!(delay <= policy.delay(attempt).mul_f64(1.25));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_lifecycle_events() {
+        let (transport, mut servers) = MockTransport::new();
+        let policy = ReconnectPolicy {
+            max_retries: Some(2),
+            ..ReconnectPolicy::default()
+        };
+        let ws = HlWs::new(transport.clone(), false).with_reconnect_policy(policy);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let handle = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), tx, None)
+            .await
+            .unwrap();
+
+        let mut server = servers.recv().await.unwrap();
+        subscribed(&mut server).await;
+        assert!(matches!(rx.recv().await, Some(StreamEvent::Connected)));
+
+        drop(server);
+        match rx.recv().await {
+            Some(StreamEvent::Disconnected { reason }) => {
+                assert!(reason.contains("Connection closed by peer"))
+            }
+            other => panic!("Expected Disconnected, got {:?}", other),
+        }
+        let mut server = servers.recv().await.unwrap();
+        subscribed(&mut server).await;
+        assert!(matches!(rx.recv().await, Some(StreamEvent::Resubscribed)));
+        // Data marks the connection healthy, which resets the retry budget
+        server.send(trade_message("BTC", 1));
+        assert_eq!(next_trade(&mut rx).await.tid, 1);
+
+        // Refused connections exhaust the retry budget
+        transport.refuse();
+        drop(server);
+        assert!(matches!(
+            rx.recv().await,
+            Some(StreamEvent::Disconnected { .. })
+        ));
+        assert!(matches!(rx.recv().await, Some(StreamEvent::GaveUp)));
+        let mut updates = handle.state_updates();
+        updates
+            .wait_for(|state| *state == ConnectionState::Closed)
+            .await
+            .unwrap();
+        assert_eq!(transport.connects(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_flapping_connection_gives_up() {
+        let (transport, mut servers) = MockTransport::new();
+        let policy = ReconnectPolicy {
+            max_retries: Some(2),
+            ..ReconnectPolicy::default()
+        };
+        let ws = HlWs::new(transport.clone(), false).with_reconnect_policy(policy);
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let _btc = ws
+            .subscribe(StreamKind::Trades, Some("BTC"), tx, None)
+            .await
+            .unwrap();
+
+        // Every connection is accepted and closed straight away
+        tokio::spawn(async move { while servers.recv().await.is_some() {} });
+        let mut disconnects = 0;
+        loop {
+            match rx.recv().await {
+                Some(StreamEvent::Connected | StreamEvent::Resubscribed) => {}
+                Some(StreamEvent::Disconnected { .. }) => disconnects += 1,
+                Some(StreamEvent::GaveUp) => break,
+                other => panic!("Expected lifecycle event, got {:?}", other),
+            }
+        }
+        assert_eq!(disconnects, 3);
+        assert_eq!(transport.connects(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_post_requests() {
+        let (transport, mut servers) = MockTransport::new();
+        let ws = HlWs::new(transport.clone(), false);
+
+        let server = async {
+            let mut server = servers.recv().await.unwrap();
+            let mut requests: Vec<Value> = Vec::new();
+            for _ in 0..3 {
+                let msg: Value = serde_json::from_str(&server.sent.recv().await.unwrap()).unwrap();
+                assert_eq!(msg["method"], "post");
+                requests.push(msg);
+            }
+            // Answer out of order; responses are matched by id
+            for msg in requests.into_iter().rev() {
+                let response = match msg["request"]["type"].as_str() {
+                    Some("info") => json!({
+                        "type": "info",
+                        "payload": {"type": "allMids", "data": {"mids": {"BTC": "65000.0"}}}
+                    }),
+                    _ if msg["request"]["payload"]["action"]["type"] == "order" => json!({
+                        "type": "action",
+                        "payload": {
+                            "status": "ok",
+                            "response": {"type": "order", "data": {"statuses": [{"resting": {"oid": 77}}]}}
+                        }
+                    }),
+                    _ => json!({"type": "error", "payload": "Invalid nonce"}),
+                };
+                let reply =
+                    json!({"channel": "post", "data": {"id": msg["id"], "response": response}});
+                server.send(reply.to_string().into_bytes());
+            }
+            server
+        };
+        let (order, info, cancel, server) = tokio::join!(
+            ws.post_action(json!({"action": {"type": "order"}, "nonce": 1})),
+            ws.post_info(json!({"type": "allMids"})),
+            ws.post_action(json!({"action": {"type": "cancel"}, "nonce": 2})),
+            server
+        );
+        assert_eq!(order.unwrap()["data"]["statuses"][0]["resting"]["oid"], 77);
+        assert_eq!(info.unwrap()["mids"]["BTC"], "65000.0");
+        match cancel {
+            Err(DexError::Exchange { msg, .. }) => assert_eq!(msg, "Invalid nonce"),
+            other => panic!("Expected exchange error, got {:?}", other),
+        }
+
+        // The socket stays open for later posts; an unanswered one times out
+        let start = Instant::now();
+        let (result, _) = tokio::join!(ws.post_info(json!({"type": "meta"})), async {
+            let mut server = server;
+            server.sent.recv().await.unwrap();
+            server
+        });
+        assert!(matches!(result, Err(DexError::Timeout)));
+        assert_eq!(start.elapsed(), POST_TIMEOUT);
+        assert_eq!(transport.connects(), 1);
+    }
+
+    /// Test side of a mock connection: messages the client sent, and a feed of
+    /// messages for the client to read (dropping it closes the connection)
+    struct MockServer {
+        sent: mpsc::UnboundedReceiver<String>,
+        incoming: mpsc::UnboundedSender<Vec<u8>>,
+    }
+
+    impl MockServer {
+        fn send(&self, msg: Vec<u8>) {
+            self.incoming.send(msg).unwrap();
+        }
+    }
+
+    #[derive(Clone)]
+    struct MockTransport {
+        servers: mpsc::UnboundedSender<MockServer>,
+        connects: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        refused: std::sync::Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl MockTransport {
+        fn new() -> (Self, mpsc::UnboundedReceiver<MockServer>) {
+            let (servers, rx) = mpsc::unbounded_channel();
+            let transport = MockTransport {
+                servers,
+                connects: Default::default(),
+                refused: Default::default(),
+            };
+            (transport, rx)
+        }
+
+        fn connects(&self) -> usize {
+            self.connects.load(std::sync::atomic::Ordering::SeqCst)
+        }
+
+        /// Fail every later connection attempt
+        fn refuse(&self) {
+            self.refused
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl WsTransport for MockTransport {
+        async fn connect(
+            &self,
+            _url: &str,
+        ) -> Result<Box<dyn WsConnection + Send + Sync + Unpin>, DexError> {
+            self.connects
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if self.refused.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(DexError::Ws("Connection refused".into()));
+            }
+            let (sent_tx, sent) = mpsc::unbounded_channel();
+            let (incoming, incoming_rx) = mpsc::unbounded_channel();
+            self.servers
+                .send(MockServer { sent, incoming })
+                .map_err(|_| DexError::Ws("test finished".into()))?;
+            Ok(Box::new(MockConnection {
+                reader: MockReader(incoming_rx),
+                writer: MockWriter(sent_tx),
+            }))
+        }
+    }
+
+    struct MockConnection {
+        reader: MockReader,
+        writer: MockWriter,
+    }
+
+    struct MockReader(mpsc::UnboundedReceiver<Vec<u8>>);
+
+    struct MockWriter(mpsc::UnboundedSender<String>);
+
+    #[async_trait]
+    impl WsConnection for MockConnection {
+        async fn read_message(&mut self) -> Result<Vec<u8>, DexError> {
+            self.reader.read_message().await
+        }
+
+        async fn send_message(&mut self, data: Bytes) -> Result<(), DexError> {
+            self.writer.send_message(data).await
+        }
+
+        async fn close(&mut self) -> Result<(), DexError> {
+            Ok(())
+        }
+
+        fn split(self: Box<Self>) -> (Box<dyn WsReader>, Box<dyn WsWriter>) {
+            (Box::new(self.reader), Box::new(self.writer))
+        }
+    }
+
+    #[async_trait]
+    impl WsReader for MockReader {
+        async fn read_message(&mut self) -> Result<Vec<u8>, DexError> {
+            self.0
+                .recv()
+                .await
+                .ok_or_else(|| DexError::Ws("Connection closed by peer".into()))
+        }
+    }
+
+    #[async_trait]
+    impl WsWriter for MockWriter {
+        async fn send_message(&mut self, data: Bytes) -> Result<(), DexError> {
+            let msg = String::from_utf8(data.to_vec()).unwrap();
+            self.0
+                .send(msg)
+                .map_err(|_| DexError::Ws("Connection closed".into()))
+        }
+
+        async fn close(&mut self) -> Result<(), DexError> {
+            Ok(())
+        }
+    }
+
     // Dummy transport for testing parsing functions
     #[derive(Clone)]
     struct DummyTransport;
@@ -647,8 +3353,7 @@ mod tests {
         async fn connect(
             &self,
             _url: &str,
-        ) -> Result<Box<dyn dex_rs_core::ws::WsConnection + Send + Sync + Unpin>, DexError>
-        {
+        ) -> Result<Box<dyn WsConnection + Send + Sync + Unpin>, DexError> {
             Err(DexError::Unsupported("DummyTransport"))
         }
     }
@@ -27,6 +27,7 @@ async fn fetch_and_trade() {
             tif: Tif::Ioc,
             reduce_only: false,
             cloid: None,
+            trigger: None,
         })
         .await
         .unwrap();
@@ -67,21 +68,17 @@ async fn test_websocket_streams() {
     let (tx, mut rx) = mpsc::unbounded_channel();
 
     // Subscribe to BTC trades
-    hl.subscribe(StreamKind::Trades, Some("BTC"), tx.clone())
+    let _trades = hl
+        .subscribe(StreamKind::Trades, Some("BTC"), tx.clone())
         .await
         .unwrap();
 
     // Wait for some messages
     let mut count = 0;
     while count < 3 {
-        if let Some(event) = rx.recv().await {
-            match event {
-                StreamEvent::Trade(trade) => {
-                    assert_eq!(trade.id.len() > 0, true);
-                    count += 1;
-                }
-                _ => {}
-            }
+        if let Some(StreamEvent::Trade(trade)) = rx.recv().await {
+            assert!(!trade.id.is_empty());
+            count += 1;
         }
     }
 }
@@ -63,6 +63,30 @@ pub struct OrderBook {
     pub asks: Vec<OrderBookLevel>,
 }
 
+/// Price bucketing of an order book: levels are rounded to `n_sig_figs`
+/// significant figures, and with 5 figures to a multiple of `mantissa`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct BookAggregation {
+    pub n_sig_figs: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mantissa: Option<u8>,
+}
+
+impl BookAggregation {
+    pub fn sig_figs(n_sig_figs: u8) -> Self {
+        Self {
+            n_sig_figs,
+            mantissa: None,
+        }
+    }
+
+    pub fn with_mantissa(mut self, mantissa: u8) -> Self {
+        self.mantissa = Some(mantissa);
+        self
+    }
+}
+
 /* -------- account-trading prereqs -------- */
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum Tif {
@@ -71,6 +95,56 @@ pub enum Tif {
     Alo,
 }
 
+/// Whether a trigger order takes profit or stops a loss
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Tpsl {
+    Tp,
+    Sl,
+}
+
+/// Trigger condition that turns an order into a stop-loss or take-profit
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Trigger {
+    pub trigger_px: Price,
+    /// Execute as a market order once triggered, otherwise as a limit at `px`
+    pub is_market: bool,
+    pub tpsl: Tpsl,
+}
+
+impl Trigger {
+    pub fn stop_market(trigger_px: Price) -> Self {
+        Self {
+            trigger_px,
+            is_market: true,
+            tpsl: Tpsl::Sl,
+        }
+    }
+
+    pub fn stop_limit(trigger_px: Price) -> Self {
+        Self {
+            trigger_px,
+            is_market: false,
+            tpsl: Tpsl::Sl,
+        }
+    }
+
+    pub fn take_profit_market(trigger_px: Price) -> Self {
+        Self {
+            trigger_px,
+            is_market: true,
+            tpsl: Tpsl::Tp,
+        }
+    }
+
+    pub fn take_profit_limit(trigger_px: Price) -> Self {
+        Self {
+            trigger_px,
+            is_market: false,
+            tpsl: Tpsl::Tp,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct OrderReq {
     pub coin: String,
@@ -80,6 +154,9 @@ pub struct OrderReq {
     pub tif: Tif,
     pub reduce_only: bool,
     pub cloid: Option<String>,
+    /// Makes this a trigger order; `tif` is ignored when set
+    #[serde(default)]
+    pub trigger: Option<Trigger>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -91,14 +168,102 @@ pub struct OrderResponse {
     pub client_order_id: String,
 }
 
+/// How the exchange should treat a batch of orders
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Grouping {
+    /// Independent orders
+    #[default]
+    Na,
+    /// Entry order followed by TP/SL orders tied to its fill
+    NormalTpsl,
+    /// TP/SL orders tied to the existing position
+    PositionTpsl,
+}
+
+/// Per-order result of an order placement
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum OrderStatusResult {
+    Resting {
+        oid: OrderId,
+    },
+    Filled {
+        oid: OrderId,
+        total_sz: Qty,
+        avg_px: Price,
+    },
+    /// TP/SL child order waiting for its parent order to fill
+    WaitingForFill,
+    /// TP/SL child order waiting for its trigger price
+    WaitingForTrigger,
+    Error(String),
+}
+
+/// Reference to an order by exchange order ID or client order ID
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum OrderRef {
+    Oid(OrderId),
+    Cloid(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CancelReq {
+    pub coin: String,
+    pub order: OrderRef,
+}
+
+/// Replace a resting order's parameters, keeping its place in the book where possible
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModifyReq {
+    pub order: OrderRef,
+    pub req: OrderReq,
+}
+
+/// Per-order result of a cancel request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CancelStatus {
+    Success,
+    Error(String),
+}
+
+/// Exchange-side TWAP order: `qty` is worked in slices over `minutes`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TwapReq {
+    pub coin: String,
+    pub is_buy: bool,
+    pub qty: Qty,
+    pub reduce_only: bool,
+    pub minutes: u32,
+    /// Randomize slice timing and size
+    pub randomize: bool,
+}
+
 /* -------- extended API types -------- */
 
+/// Margin mode of a position
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LeverageType {
+    Cross,
+    Isolated,
+}
+
+/// Leverage setting of a position, as `{"type": "cross", "value": 10}`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Leverage {
+    #[serde(rename = "type")]
+    pub kind: LeverageType,
+    pub value: u32,
+    /// Isolated positions only
+    #[serde(rename = "rawUsd", default, skip_serializing_if = "Option::is_none")]
+    pub raw_usd: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AssetPosition {
     pub coin: String,
     pub hold: String,
     pub szi: String,
-    pub leverage: Option<FundingRate>,
+    pub leverage: Option<Leverage>,
     pub entry_px: Option<Price>,
     pub position_value: String,
     pub unrealized_pnl: String,
@@ -121,18 +286,13 @@ pub struct CrossMarginSummary {
     pub total_raw_usd: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub struct WithdrawalsUsed {
-    pub used: String,
-    pub limit: String,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct UserState {
     pub asset_positions: Vec<AssetPosition>,
     pub cross_margin_summary: CrossMarginSummary,
     pub cross_maintenance_margin_used: String,
-    pub withdrawals_used: Vec<WithdrawalsUsed>,
+    /// USDC that can be withdrawn now
+    pub withdrawable: String,
     pub time: u64,
 }
 
@@ -149,6 +309,7 @@ pub struct OpenOrder {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub struct UserFill {
     pub coin: String,
     pub px: String,
@@ -166,6 +327,48 @@ pub struct UserFill {
     pub liquidation: Option<bool>,
 }
 
+/// Progress of a TWAP order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapState {
+    pub coin: String,
+    pub user: String,
+    pub side: String,
+    pub sz: String,
+    pub executed_sz: String,
+    pub executed_ntl: String,
+    pub minutes: u32,
+    pub reduce_only: bool,
+    pub randomize: bool,
+    pub timestamp: u64,
+}
+
+/// Lifecycle status of a TWAP order (`activated`, `finished`, `terminated` or `error`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TwapStatus {
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapHistoryEntry {
+    pub time: u64,
+    pub state: TwapState,
+    pub status: TwapStatus,
+    #[serde(default)]
+    pub twap_id: Option<u64>,
+}
+
+/// A single slice fill of a TWAP order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TwapSliceFill {
+    pub fill: UserFill,
+    pub twap_id: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FundingHistory {
     pub coin: String,
@@ -228,10 +431,14 @@ pub struct MetaAndAssetCtxs {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SpotAssetMeta {
     pub name: String,
+    #[serde(rename = "szDecimals")]
     pub sz_decimals: u32,
+    #[serde(rename = "weiDecimals")]
     pub wei_decimals: u32,
     pub index: u32,
+    #[serde(rename = "tokenId")]
     pub token_id: String,
+    #[serde(rename = "isCanonical")]
     pub is_canonical: bool,
 }
 
@@ -246,6 +453,7 @@ pub struct SpotUniverseItem {
     pub tokens: Vec<u32>,
     pub name: String,
     pub index: u32,
+    #[serde(rename = "isCanonical")]
     pub is_canonical: bool,
 }
 
@@ -265,6 +473,24 @@ pub struct SpotMetaAndAssetCtxs {
     pub asset_ctxs: Vec<SpotAssetCtx>,
 }
 
+/// A user's spot token balances
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpotBalances {
+    pub balances: Vec<SpotBalance>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SpotBalance {
+    pub coin: String,
+    /// Token index in spot meta
+    pub token: u32,
+    pub total: String,
+    /// Amount reserved by open orders
+    pub hold: String,
+    #[serde(rename = "entryNtl")]
+    pub entry_ntl: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct AllMids {
     pub mids: std::collections::HashMap<String, String>,
@@ -288,6 +514,60 @@ pub struct Candle {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct CandleSnapshot(pub Vec<Candle>);
 
+/// Candle width, serialized as the exchange's interval string (`1m`, `4h`, `1M`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum CandleInterval {
+    #[serde(rename = "1m")]
+    Min1,
+    #[serde(rename = "3m")]
+    Min3,
+    #[serde(rename = "5m")]
+    Min5,
+    #[serde(rename = "15m")]
+    Min15,
+    #[serde(rename = "30m")]
+    Min30,
+    #[serde(rename = "1h")]
+    Hour1,
+    #[serde(rename = "2h")]
+    Hour2,
+    #[serde(rename = "4h")]
+    Hour4,
+    #[serde(rename = "8h")]
+    Hour8,
+    #[serde(rename = "12h")]
+    Hour12,
+    #[serde(rename = "1d")]
+    Day1,
+    #[serde(rename = "3d")]
+    Day3,
+    #[serde(rename = "1w")]
+    Week1,
+    #[serde(rename = "1M")]
+    Month1,
+}
+
+impl CandleInterval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CandleInterval::Min1 => "1m",
+            CandleInterval::Min3 => "3m",
+            CandleInterval::Min5 => "5m",
+            CandleInterval::Min15 => "15m",
+            CandleInterval::Min30 => "30m",
+            CandleInterval::Hour1 => "1h",
+            CandleInterval::Hour2 => "2h",
+            CandleInterval::Hour4 => "4h",
+            CandleInterval::Hour8 => "8h",
+            CandleInterval::Hour12 => "12h",
+            CandleInterval::Day1 => "1d",
+            CandleInterval::Day3 => "3d",
+            CandleInterval::Week1 => "1w",
+            CandleInterval::Month1 => "1M",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct OrderStatus {
     pub order: Option<OpenOrder>,
@@ -378,6 +658,18 @@ mod tests {
         assert_eq!(t, back);
     }
 
+    #[test]
+    fn test_candle_interval_serde() {
+        for interval in [CandleInterval::Min15, CandleInterval::Month1] {
+            let j = serde_json::to_string(&interval).unwrap();
+            assert_eq!(j, format!("\"{}\"", interval.as_str()));
+            assert_eq!(
+                serde_json::from_str::<CandleInterval>(&j).unwrap(),
+                interval
+            );
+        }
+    }
+
     #[test]
     fn test_generate_cloid() {
         let cloid1 = generate_cloid();
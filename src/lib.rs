@@ -1,11 +1,13 @@
 //! Public façade crate for the whole SDK.
 
-pub use dex_rs_core::{DexError, PerpDex, StreamEvent, StreamKind};
+pub use dex_rs_core::{
+    ConnectionState, DexError, PerpDex, StreamEvent, StreamKind, SubscriptionHandle,
+};
 pub use dex_rs_types as types;
 pub type DexResult<T> = Result<T, DexError>;
 
 #[cfg(feature = "hyperliquid")]
-pub use dex_rs_hyperliquid::Hyperliquid;
+pub use dex_rs_hyperliquid::{Hyperliquid, OrderTransport, ReconnectPolicy};
 
 /// Commonly-used imports in a single glob.
 pub mod prelude {